@@ -1,15 +1,91 @@
 #![allow(dead_code)]
 
+use std::cmp::Ordering;
 use std::ops::{Add, Sub, Mul, Div};
 
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate toml;
+
 pub mod tuning;
+#[cfg(feature = "serde")]
+pub mod units;
 
 /// Hertz is the standard unit of frequency.
 /// 
 /// It is also the standard unit of pitch as well.
+///
+/// `Hz` has the same layout as `f32`, so it can be passed through FFI as is.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(transparent)]
 pub struct Hz(pub f32);
 
+impl Hz {
+    /// Returns the interval from `reference` up to the pitch
+    ///
+    /// Same as `self / reference`: `Hz(660.0).cents_from(Hz(440.0))` is a pure fifth.
+    /// The interval is negative if the pitch is below the reference.
+    pub fn cents_from(self, reference: Hz) -> Cents {
+        self / reference
+    }
+
+    /// Returns the interval from `target` to the pitch, positive when the pitch is sharp
+    pub fn deviation(self, target: Hz) -> Cents {
+        self / target
+    }
+
+    /// Checks if the pitch is within `tolerance` of `target`, like a tuner would
+    pub fn in_tune(self, target: Hz, tolerance: Cents) -> bool {
+        self.deviation(target).abs().0 <= tolerance.0
+    }
+
+    /// Lowers the pitch by `interval`, but not below `floor`
+    pub fn sub_clamped(self, interval: Cents, floor: Hz) -> Hz {
+        Hz((self - interval).0.max(floor.0))
+    }
+
+    /// Finds the ratio closest to the interval from `other` to the pitch
+    ///
+    /// Ratios are searched up to the denominator `max_den`. Returns the ratio and the interval
+    /// from it to the actual interval, so `Hz(550.0).named_interval_to(Hz(440.0), 8)` is 5/4
+    /// with no error.
+    ///
+    /// Both pitches must be positive and finite to make an interval. Otherwise, like for
+    /// an empty bin of a spectrum, the ratio is 1/1 and the error is NaN.
+    pub fn named_interval_to(self, other: Hz, max_den: u32) -> ((u32, u32), Cents) {
+        let valid = |hz: Hz| hz.0.is_finite() && hz.0 > 0.0;
+        if !valid(self) || !valid(other) { return ((1, 1), Cents(f32::NAN)) }
+
+        let interval = self / other;
+        let (n, d) = best_ratio(interval, max_den);
+        (
+            (n, d),
+            interval - Cents((1200.0 * (f64::from(n) / f64::from(d)).log2()) as f32)
+        )
+    }
+
+    /// Finds the harmonic of `fundamental` nearest to the pitch
+    ///
+    /// Returns the harmonic number (at least 1) and the interval from the harmonic to the pitch.
+    pub fn nearest_harmonic(self, fundamental: Hz) -> (u32, Cents) {
+        let n = (self.0 / fundamental.0).round().max(1.0) as u32;
+        (n, self / (fundamental * n as f32))
+    }
+
+    /// Views a slice of `Hz` as a slice of `f32` without copying
+    pub fn as_f32_slice(slice: &[Hz]) -> &[f32] {
+        // SAFETY: `Hz` is `repr(transparent)` over `f32`
+        unsafe { ::std::slice::from_raw_parts(slice.as_ptr() as *const f32, slice.len()) }
+    }
+
+    /// Views a slice of `f32` as a slice of `Hz` without copying
+    pub fn from_f32_slice(slice: &[f32]) -> &[Hz] {
+        // SAFETY: `Hz` is `repr(transparent)` over `f32`
+        unsafe { ::std::slice::from_raw_parts(slice.as_ptr() as *const Hz, slice.len()) }
+    }
+}
+
 /// You can add an interval to Hz. For example:
 ///
 /// ```rust
@@ -39,6 +115,23 @@ impl Mul<f32> for Hz {
     }
 }
 
+impl Mul<Hz> for f32 {
+    type Output = Hz;
+    fn mul(self, rhs: Hz) -> Self::Output {
+        Hz(self * rhs.0)
+    }
+}
+
+/// `Hz(b) - Hz(a)` is the interval from `a` to `b`, the same as `Hz(b) / Hz(a)`.
+///
+/// It is not the difference of frequencies, which is `Hz(b.0 - a.0)`.
+impl Sub<Hz> for Hz {
+    type Output = Cents;
+    fn sub(self, rhs: Hz) -> Self::Output {
+        Div::div(self, rhs)
+    }
+}
+
 impl Div<Hz> for Hz {
     /// `Hz(b) / Hz(a)` is equivalent to `Cents::from_ratio(b / a)`.
     type Output = Cents;
@@ -50,17 +143,181 @@ impl Div<Hz> for Hz {
 /// Cent is the standard unit of musical interval.
 /// 
 /// 12 EDO semitone is 100 cents large while an octave is 1200 cents large.
+///
+/// `Cents` has the same layout as `f32`, so it can be passed through FFI as is.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(transparent)]
 pub struct Cents(pub f32);
 
 impl Cents {
+    /// The octave, 2:1
+    pub const OCTAVE: Cents = Cents(1200.0);
+
     pub fn from_ratio(ratio: f32) -> Cents {
         Cents(1200.0 * ratio.log2())
     }
 
+    /// Like `from_ratio`, but fails for ratios that don't make an interval
+    pub fn try_from_ratio(ratio: f32) -> Result<Cents, RatioError> {
+        if !ratio.is_finite() { return Err(RatioError::NotFinite) }
+        if ratio <= 0.0 { return Err(RatioError::NonPositive) }
+
+        Ok(Cents::from_ratio(ratio))
+    }
+
     pub fn to_ratio(self) -> f32 {
         (self.0 / 1200.0).exp2()
     }
+
+    /// Returns the interval reduced into an octave, `[0, 1200)`
+    pub fn pitch_class(self) -> Cents {
+        Cents(self.0.rem_euclid(1200.0))
+    }
+
+    /// Returns the signed distance from the nearest octave of the unison, `(-600, 600]`
+    ///
+    /// Unlike `pitch_class`, intervals just below an octave become negative: 1190 cents is -10.
+    pub fn nearest_unison_offset(self) -> Cents {
+        let class = self.pitch_class().0;
+        Cents(if class > 600.0 { class - 1200.0 } else { class })
+    }
+
+    /// Returns the number of full octaves in the interval, rounding downwards
+    ///
+    /// `Cents(c.octave_number() as f32 * 1200.0) + c.pitch_class()` is `c`.
+    pub fn octave_number(self) -> i32 {
+        (self.0 / 1200.0).floor() as i32
+    }
+
+    /// Returns the best ratios just below and just above the interval
+    ///
+    /// The ratios are found by descending the Stern-Brocot tree until the denominator
+    /// exceeds `max_den` (or the numerator exceeds `u32::MAX`). If the interval is exactly
+    /// a ratio, both neighbors are that ratio. A non-finite interval has no neighbors but
+    /// the ends of the tree, `(0, 1)` and `(1, 0)`.
+    pub fn stern_brocot_neighbors(self, max_den: u32) -> ((u32, u32), (u32, u32)) {
+        let x = (f64::from(self.0) / 1200.0).exp2();
+        if !x.is_finite() { return ((0, 1), (1, 0)) }
+
+        let (max_num, max_den) = (u64::from(u32::MAX), u64::from(max_den));
+        let (mut lower, mut upper): ((u64, u64), (u64, u64)) = ((0, 1), (1, 0));
+        let value = |(n, d): (u64, u64)| n as f64 / d as f64;
+        // How many times `step` can be added to `from` within the bounds
+        let room = |from: (u64, u64), step: (u64, u64)| {
+            let by_num = (max_num - from.0).checked_div(step.0).unwrap_or(u64::MAX);
+            let by_den = (max_den - from.1).checked_div(step.1).unwrap_or(u64::MAX);
+            by_num.min(by_den)
+        };
+
+        // Descend a whole run of moves in the same direction at once, like continued fractions do
+        loop {
+            let (num, den) = (lower.0 + upper.0, lower.1 + upper.1);
+            if den > max_den || num > max_num { break }
+
+            let mediant = value((num, den));
+            if mediant == x { return ((num as u32, den as u32), (num as u32, den as u32)) }
+
+            if mediant < x {
+                // `lower + k * upper` stays below `x` for `k < (x * ld - ln) / (un - x * ud)`
+                let estimate = (x * lower.1 as f64 - lower.0 as f64) / (upper.0 as f64 - x * upper.1 as f64);
+                let mut k = (estimate as u64).clamp(1, room(lower, upper));
+                while k > 1 && value((lower.0 + k * upper.0, lower.1 + k * upper.1)) >= x { k -= 1 }
+                lower = (lower.0 + k * upper.0, lower.1 + k * upper.1);
+            }
+            else {
+                // `upper + k * lower` stays above `x` for `k < (un - x * ud) / (x * ld - ln)`
+                let estimate = (upper.0 as f64 - x * upper.1 as f64) / (x * lower.1 as f64 - lower.0 as f64);
+                let mut k = (estimate as u64).clamp(1, room(upper, lower));
+                while k > 1 && value((upper.0 + k * lower.0, upper.1 + k * lower.1)) <= x { k -= 1 }
+                upper = (upper.0 + k * lower.0, upper.1 + k * lower.1);
+            }
+        }
+
+        ((lower.0 as u32, lower.1 as u32), (upper.0 as u32, upper.1 as u32))
+    }
+
+    /// Stacks the interval `n` times, like `self * n as f32` but usable in constants
+    ///
+    /// ```rust
+    /// # use monochord::Cents;
+    /// const TWELVE_FIFTHS: Cents = Cents(700.0).times(12);
+    /// ```
+    pub const fn times(self, n: i32) -> Cents {
+        Cents(self.0 * n as f32)
+    }
+
+    /// Returns the comma between two stacks of intervals, `sum(a) - sum(b)`
+    pub fn comma_between(a: &[Cents], b: &[Cents]) -> Cents {
+        let sum = |cs: &[Cents]| cs.iter().fold(Cents(0.0), |acc, &c| acc + c);
+        sum(a) - sum(b)
+    }
+
+    /// Checks if two intervals are the same up to octaves, within `tolerance`
+    ///
+    /// Intervals just below an octave match intervals just above the unison.
+    pub fn same_class(self, other: Cents, tolerance: Cents) -> bool {
+        let d = (self - other).pitch_class().0;
+        d.min(1200.0 - d) <= tolerance.0
+    }
+
+    /// Views a slice of `Cents` as a slice of `f32` without copying
+    pub fn as_f32_slice(slice: &[Cents]) -> &[f32] {
+        // SAFETY: `Cents` is `repr(transparent)` over `f32`
+        unsafe { ::std::slice::from_raw_parts(slice.as_ptr() as *const f32, slice.len()) }
+    }
+
+    /// Views a slice of `f32` as a slice of `Cents` without copying
+    pub fn from_f32_slice(slice: &[f32]) -> &[Cents] {
+        // SAFETY: `Cents` is `repr(transparent)` over `f32`
+        unsafe { ::std::slice::from_raw_parts(slice.as_ptr() as *const Cents, slice.len()) }
+    }
+
+    /// Returns the size of the interval regardless of its direction
+    pub fn abs(self) -> Cents {
+        Cents(self.0.abs())
+    }
+
+    /// Returns `1.0` for upward intervals, `-1.0` for downward ones and `0.0` for the unison
+    pub fn signum(self) -> f32 {
+        if self.0 == 0.0 { 0.0 } else { self.0.signum() }
+    }
+
+    /// Names the comma the interval is, if it's within `tolerance` of a known one
+    ///
+    /// The known commas are the syntonic comma (81/80), the Pythagorean comma
+    /// (3^12/2^19), the schisma (32805/32768), the diaschisma (2048/2025) and the
+    /// lesser diesis (128/125). The sign of the interval is ignored and the closest comma wins.
+    pub fn classify_comma(self, tolerance: Cents) -> Option<&'static str> {
+        const COMMAS: [(&str, f32); 5] = [
+            ("syntonic comma", 21.506),
+            ("Pythagorean comma", 23.460),
+            ("schisma", 1.954),
+            ("diaschisma", 19.553),
+            ("diesis", 41.059),
+        ];
+
+        let size = self.abs().0;
+        COMMAS.iter()
+            .map(|&(name, cents)| (name, (size - cents).abs()))
+            .filter(|&(_, error)| error <= tolerance.0)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .map(|(name, _)| name)
+    }
+
+    /// Rounds the interval to the nearest multiple of `resolution` cents, making a hashable key
+    pub fn to_key(self, resolution: f32) -> CentsKey {
+        CentsKey((self.0 / resolution).round() as i32)
+    }
+
+    /// Returns the control voltage of the interval at 1 V/octave
+    pub fn per_volt_octave(self) -> f32 {
+        self.0 / Cents::OCTAVE.0
+    }
+
+    /// Makes an interval from a control voltage at 1 V/octave
+    pub fn from_volts(volts: f32) -> Cents {
+        Cents(volts * Cents::OCTAVE.0)
+    }
 }
 
 impl Add<Cents> for Cents {
@@ -84,6 +341,245 @@ impl Mul<f32> for Cents {
     }
 }
 
+impl Mul<Cents> for f32 {
+    type Output = Cents;
+    fn mul(self, rhs: Cents) -> Self::Output {
+        Cents(self * rhs.0)
+    }
+}
+
+/// Returns the intervals between all pairs of pitches
+///
+/// `interval_matrix(pitches)[i][j]` is the interval from `pitches[i]` to `pitches[j]`.
+pub fn interval_matrix(pitches: &[Hz]) -> Vec<Vec<Cents>> {
+    pitches.iter()
+        .map(|&from| pitches.iter().map(|&to| to / from).collect())
+        .collect()
+}
+
+/// Finds the implied fundamental of a chord
+///
+/// Intervals from the first pitch are approximated by ratios with denominators up to
+/// `max_den`, which puts all pitches on a common harmonic series. Returns its first harmonic.
+///
+/// Returns `Hz(f32::NAN)` if there are no pitches, if some pitch is not positive and finite,
+/// or if the harmonic series is too fine to compute (with large `max_den`).
+pub fn virtual_fundamental(pitches: &[Hz], max_den: u32) -> Hz {
+    let base = match pitches.first() {
+        Some(&hz) => hz,
+        None => return Hz(f32::NAN),
+    };
+    if pitches.iter().any(|hz| !hz.0.is_finite() || hz.0 <= 0.0) { return Hz(f32::NAN) }
+
+    let ratios: Vec<(u64, u64)> = pitches.iter()
+        .map(|&hz| best_ratio(hz / base, max_den))
+        .map(|(n, d)| (u64::from(n), u64::from(d)))
+        .collect();
+
+    // Harmonic numbers of the pitches are `n * lcm / d`, with the base being `lcm`
+    let lcm = ratios.iter().try_fold(1, |acc: u64, &(_, d)| (acc / gcd(acc, d)).checked_mul(d));
+    let common = lcm.and_then(|lcm| {
+        ratios.iter().try_fold(lcm, |acc, &(n, d)| Some(gcd(acc, n.checked_mul(lcm)? / d)))
+    });
+
+    match (common, lcm) {
+        (Some(common), Some(lcm)) => base * (common as f32 / lcm as f32),
+        _ => Hz(f32::NAN),
+    }
+}
+
+/// Returns whichever Stern-Brocot neighbor of the interval is closer to it
+fn best_ratio(interval: Cents, max_den: u32) -> (u32, u32) {
+    let (lower, upper) = interval.stern_brocot_neighbors(max_den.max(1));
+    let error = |(n, d): (u32, u32)| {
+        if d == 0 || n == 0 { return f64::INFINITY }
+        ((f64::from(n) / f64::from(d)).log2() * 1200.0 - f64::from(interval.0)).abs()
+    };
+
+    if error(lower) <= error(upper) { lower } else { upper }
+}
+
+/// An exact frequency ratio, kept in lowest terms.
+///
+/// Like intervals in cents, ratios are added by multiplying them:
+///
+/// ```rust
+/// # use monochord::*;
+/// Ratio::new(3, 2) + Ratio::new(4, 3); // Ratio::new(2, 1)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ratio {
+    num: u32,
+    den: u32,
+}
+
+impl Ratio {
+    /// Creates a ratio `num/den` reduced to lowest terms. Panics if either part is zero.
+    pub fn new(num: u32, den: u32) -> Ratio {
+        Self::reduced(u64::from(num), u64::from(den))
+    }
+
+    fn reduced(num: u64, den: u64) -> Ratio {
+        assert!(num != 0 && den != 0, "a ratio can't be zero or infinite");
+
+        let d = gcd(num, den);
+        let (num, den) = (num / d, den / d);
+        assert!(num <= u64::from(u32::MAX) && den <= u64::from(u32::MAX), "ratio overflow");
+
+        Ratio { num: num as u32, den: den as u32 }
+    }
+
+    pub fn numer(self) -> u32 {
+        self.num
+    }
+
+    pub fn denom(self) -> u32 {
+        self.den
+    }
+
+    pub fn to_cents(self) -> Cents {
+        Cents((1200.0 * (f64::from(self.num) / f64::from(self.den)).log2()) as f32)
+    }
+
+    /// Returns the inverted ratio, `den/num`
+    pub fn recip(self) -> Ratio {
+        Ratio { num: self.den, den: self.num }
+    }
+
+    fn stack(self, other: Ratio) -> Ratio {
+        let num = u64::from(self.num).checked_mul(u64::from(other.num)).expect("ratio overflow");
+        let den = u64::from(self.den).checked_mul(u64::from(other.den)).expect("ratio overflow");
+        Ratio::reduced(num, den)
+    }
+}
+
+/// Stacks two ratios (multiplies them)
+impl Add<Ratio> for Ratio {
+    type Output = Ratio;
+    fn add(self, rhs: Ratio) -> Self::Output {
+        self.stack(rhs)
+    }
+}
+
+/// Takes one ratio from another (divides them)
+impl Sub<Ratio> for Ratio {
+    type Output = Ratio;
+    fn sub(self, rhs: Ratio) -> Self::Output {
+        self.stack(rhs.recip())
+    }
+}
+
+/// Stacks a ratio `rhs` times (raises it to a power)
+impl Mul<u32> for Ratio {
+    type Output = Ratio;
+    fn mul(self, rhs: u32) -> Self::Output {
+        (0..rhs).fold(Ratio::new(1, 1), |acc, _| acc.stack(self))
+    }
+}
+
+/// `Hz` with a total order, for sorting and ordered collections
+///
+/// NaN is greater than any other frequency (so it sorts last) and equal to any other NaN.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderedHz(pub Hz);
+
+impl PartialEq for OrderedHz {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedHz {}
+
+impl PartialOrd for OrderedHz {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedHz {
+    fn cmp(&self, other: &Self) -> Ordering {
+        nan_last_cmp((self.0).0, (other.0).0)
+    }
+}
+
+/// An error that occured while making an interval from a ratio
+#[derive(Debug, Clone, PartialEq)]
+pub enum RatioError {
+    /// The ratio is zero or negative
+    NonPositive,
+    /// The ratio is infinite or NaN
+    NotFinite,
+}
+
+impl ::std::fmt::Display for RatioError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            RatioError::NonPositive => write!(f, "the ratio is not positive"),
+            RatioError::NotFinite => write!(f, "the ratio is not finite"),
+        }
+    }
+}
+
+impl ::std::error::Error for RatioError {}
+
+/// `Cents` with a total order, for sorting and ordered collections
+///
+/// NaN is greater than any other interval (so it sorts last) and equal to any other NaN.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderedCents(pub Cents);
+
+impl PartialEq for OrderedCents {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedCents {}
+
+impl PartialOrd for OrderedCents {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedCents {
+    fn cmp(&self, other: &Self) -> Ordering {
+        nan_last_cmp((self.0).0, (other.0).0)
+    }
+}
+
+/// An interval rounded to a whole number of buckets, usable as a hash map key
+///
+/// Made by `Cents::to_key`. Keys are only comparable if made with the same resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CentsKey(pub i32);
+
+impl CentsKey {
+    /// Returns the interval at the center of the bucket
+    pub fn to_cents(self, resolution: f32) -> Cents {
+        Cents(self.0 as f32 * resolution)
+    }
+}
+
+fn nan_last_cmp(a: f32, b: f32) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(&b).unwrap(),
+    }
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +604,288 @@ mod tests {
             Cents::from_ratio(3.0 / 2.0)
         );
     }
+
+    #[test] fn stern_brocot_neighbors() {
+        assert_eq!(Cents(700.0).stern_brocot_neighbors(2), ((1, 1), (3, 2)));
+        assert_eq!(Cents(700.0).stern_brocot_neighbors(5), ((7, 5), (3, 2)));
+        assert_eq!(Cents(800.0).stern_brocot_neighbors(3), ((3, 2), (5, 3)));
+        assert_eq!(Cents(600.0).stern_brocot_neighbors(12), ((7, 5), (17, 12)));
+        assert_eq!(Cents(1200.0).stern_brocot_neighbors(12), ((2, 1), (2, 1)));
+    }
+
+    #[test] fn stern_brocot_runs() {
+        // Descending one mediant at a time, as a reference
+        fn naive(cents: Cents, max_den: u32) -> ((u32, u32), (u32, u32)) {
+            let x = (f64::from(cents.0) / 1200.0).exp2();
+            let (mut lower, mut upper) = ((0, 1), (1, 0));
+            loop {
+                let (num, den) = (lower.0 + upper.0, lower.1 + upper.1);
+                if den > max_den { break }
+
+                let mediant = f64::from(num) / f64::from(den);
+                if mediant < x { lower = (num, den) }
+                else if mediant > x { upper = (num, den) }
+                else { return ((num, den), (num, den)) }
+            }
+            (lower, upper)
+        }
+
+        for i in -300..300 {
+            let cents = Cents(i as f32 * 13.7);
+            for &max_den in &[1, 2, 7, 12, 100, 1000] {
+                assert_eq!(cents.stern_brocot_neighbors(max_den), naive(cents, max_den));
+            }
+        }
+    }
+
+    #[test] fn stern_brocot_extremes() {
+        assert_eq!(Cents(f32::INFINITY).stern_brocot_neighbors(8), ((0, 1), (1, 0)));
+        assert_eq!(Cents(f32::NAN).stern_brocot_neighbors(8), ((0, 1), (1, 0)));
+        assert_eq!(Cents(f32::NEG_INFINITY).stern_brocot_neighbors(8), ((0, 1), (1, 8)));
+
+        // Past about 32 octaves the numerator runs out before the denominator
+        assert_eq!(Cents(40000.0).stern_brocot_neighbors(8), ((u32::MAX, 1), (1, 0)));
+        assert_eq!(Cents(-40000.0).stern_brocot_neighbors(8), ((0, 1), (1, 8)));
+
+        // Long runs are descended quickly even with huge denominators
+        let (lower, upper) = Cents(0.001).stern_brocot_neighbors(u32::MAX);
+        let x = (0.001_f32 as f64 / 1200.0).exp2();
+        let value = |(n, d): (u32, u32)| f64::from(n) / f64::from(d);
+        assert!(value(lower) <= x && x <= value(upper));
+        assert!(lower.1 > 1_000_000 && upper.1 > 1_000_000);
+    }
+
+    #[test] fn ratio_arithmetic() {
+        assert_eq!(Ratio::new(3, 2) + Ratio::new(4, 3), Ratio::new(2, 1));
+        assert_eq!(Ratio::new(2, 1) - Ratio::new(3, 2), Ratio::new(4, 3));
+        assert_eq!(Ratio::new(9, 8) * 2, Ratio::new(81, 64));
+        assert_eq!(Ratio::new(6, 4).numer(), 3);
+        assert_eq!((Ratio::new(3, 2) + Ratio::new(4, 3)).to_cents(), Cents(1200.0));
+    }
+
+    #[test] fn pitch_class() {
+        assert_eq!(Cents(2650.0).pitch_class(), Cents(250.0));
+        assert_eq!(Cents(2650.0).octave_number(), 2);
+        assert_eq!(Cents(-100.0).pitch_class(), Cents(1100.0));
+        assert_eq!(Cents(-100.0).octave_number(), -1);
+    }
+
+    #[test] fn scalar_times_unit() {
+        assert_eq!(2.0 * Hz(440.0), Hz(880.0));
+        assert_eq!(3.0 * Cents(100.0), Cents(300.0));
+    }
+
+    #[test] fn hz_minus_hz() {
+        assert_eq!(Hz(660.0) - Hz(440.0), Cents::from_ratio(1.5));
+    }
+
+    #[test] fn const_times() {
+        const TWELVE_FIFTHS: Cents = Cents(700.0).times(12);
+        assert_eq!(TWELVE_FIFTHS, Cents(8400.0));
+        assert_eq!(Cents(100.0).times(-3), Cents(-300.0));
+    }
+
+    #[test] fn comma_between() {
+        let fifth = Cents::from_ratio(3.0 / 2.0);
+        let third = Cents::from_ratio(5.0 / 4.0);
+        let octave = Cents(1200.0);
+
+        let syntonic = Cents::comma_between(&[fifth; 4], &[octave, octave, third]);
+        assert!((syntonic.0 - 21.506).abs() < 0.01);
+
+        let pythagorean = Cents::comma_between(&[fifth; 12], &[octave; 7]);
+        assert!((pythagorean.0 - 23.460).abs() < 0.01);
+    }
+
+    #[test] fn ordered_wrappers() {
+        let mut hzs: Vec<_> = [Hz(440.0), Hz(f32::NAN), Hz(220.0), Hz(330.0)]
+            .iter().map(|&hz| OrderedHz(hz)).collect();
+        hzs.sort();
+
+        let sorted: Vec<_> = hzs.iter().map(|hz| (hz.0).0).collect();
+        assert_eq!(&sorted[..3], &[220.0, 330.0, 440.0]);
+        assert!(sorted[3].is_nan());
+
+        let set: ::std::collections::BTreeSet<_> = [Cents(700.0), Cents(f32::NAN), Cents(-100.0)]
+            .iter().map(|&c| OrderedCents(c)).collect();
+        let first = set.iter().next().unwrap();
+        assert_eq!(first.0, Cents(-100.0));
+        assert!((set.iter().last().unwrap().0).0.is_nan());
+    }
+
+    #[test] fn same_class() {
+        assert!(Cents(1902.0).same_class(Cents(702.0), Cents(0.1)));
+        assert!(Cents(1199.0).same_class(Cents(1.0), Cents(3.0)));
+        assert!(Cents(-1.0).same_class(Cents(1.0), Cents(3.0)));
+        assert!(!Cents(1199.0).same_class(Cents(1.0), Cents(1.0)));
+        assert!(!Cents(700.0).same_class(Cents(500.0), Cents(10.0)));
+    }
+
+    #[test] fn f32_layout() {
+        use std::mem::{size_of, align_of};
+
+        const _: () = assert!(size_of::<Hz>() == size_of::<f32>());
+        const _: () = assert!(size_of::<Cents>() == size_of::<f32>());
+        assert_eq!(align_of::<Hz>(), align_of::<f32>());
+        assert_eq!(align_of::<Cents>(), align_of::<f32>());
+    }
+
+    #[test] fn f32_slices() {
+        let hzs = [Hz(220.0), Hz(440.0), Hz(880.0)];
+        assert_eq!(Hz::as_f32_slice(&hzs), &[220.0, 440.0, 880.0]);
+        assert_eq!(Hz::from_f32_slice(&[220.0, 440.0]), &[Hz(220.0), Hz(440.0)]);
+
+        let cents = [Cents(100.0), Cents(-50.0)];
+        assert_eq!(Cents::as_f32_slice(&cents), &[100.0, -50.0]);
+        assert_eq!(Cents::from_f32_slice(&[700.0]), &[Cents(700.0)]);
+    }
+
+    #[test] fn interval_matrix() {
+        let matrix = super::interval_matrix(&[Hz(440.0), Hz(550.0), Hz(660.0)]);
+        let rounded: Vec<Vec<f32>> = matrix.iter()
+            .map(|row| row.iter().map(|c| c.0.round()).collect())
+            .collect();
+
+        assert_eq!(rounded, vec![
+            vec![0.0, 386.0, 702.0],
+            vec![-386.0, 0.0, 316.0],
+            vec![-702.0, -316.0, 0.0],
+        ]);
+    }
+
+    #[test] fn nearest_harmonic() {
+        assert_eq!(Hz(1320.0).nearest_harmonic(Hz(440.0)), (3, Cents(0.0)));
+        assert_eq!(Hz(440.0).nearest_harmonic(Hz(440.0)), (1, Cents(0.0)));
+
+        let (n, error) = Hz(1325.0).nearest_harmonic(Hz(440.0));
+        assert_eq!(n, 3);
+        assert!(error.0 > 0.0 && error.0 < 10.0);
+
+        assert_eq!(Hz(100.0).nearest_harmonic(Hz(440.0)).0, 1);
+    }
+
+    #[test] fn virtual_fundamental() {
+        let triad = super::virtual_fundamental(&[Hz(440.0), Hz(550.0), Hz(660.0)], 16);
+        assert_eq!(triad.0.round(), 110.0);
+
+        let fifth = super::virtual_fundamental(&[Hz(440.0), Hz(660.0)], 16);
+        assert_eq!(fifth.0.round(), 220.0);
+
+        let octaves = super::virtual_fundamental(&[Hz(220.0), Hz(440.0), Hz(880.0)], 16);
+        assert_eq!(octaves.0.round(), 220.0);
+
+        assert!(super::virtual_fundamental(&[], 16).0.is_nan());
+        assert!(super::virtual_fundamental(&[Hz(0.0), Hz(440.0)], 8).0.is_nan());
+        assert!(super::virtual_fundamental(&[Hz(440.0), Hz(-440.0)], 8).0.is_nan());
+        assert!(super::virtual_fundamental(&[Hz(440.0), Hz(f32::INFINITY)], 8).0.is_nan());
+
+        // The common denominator overflows
+        let cluster = [Hz(440.0), Hz(441.0), Hz(443.3), Hz(447.7)];
+        assert!(super::virtual_fundamental(&cluster, 1 << 20).0.is_nan());
+    }
+
+    #[test] fn cents_abs_signum() {
+        assert_eq!(Cents(-50.0).abs(), Cents(50.0));
+        assert_eq!(Cents(50.0).abs(), Cents(50.0));
+
+        assert_eq!(Cents(700.0).signum(), 1.0);
+        assert_eq!(Cents(-700.0).signum(), -1.0);
+        assert_eq!(Cents(0.0).signum(), 0.0);
+        assert_eq!(Cents(-0.0).signum(), 0.0);
+    }
+
+    #[test] fn cents_from() {
+        assert_eq!(Hz(660.0).cents_from(Hz(440.0)), Cents::from_ratio(1.5));
+        assert_eq!(Hz(660.0).cents_from(Hz(440.0)), Hz(660.0) / Hz(440.0));
+        assert!(Hz(220.0).cents_from(Hz(440.0)).0 < 0.0);
+    }
+
+    #[test] fn volts() {
+        assert_eq!(Cents::OCTAVE.per_volt_octave(), 1.0);
+        assert_eq!(Cents(1200.0).per_volt_octave(), 1.0);
+        assert_eq!(Cents(-600.0).per_volt_octave(), -0.5);
+        assert_eq!(Cents::from_volts(2.0), Cents(2400.0));
+        assert_eq!(Cents::from_volts(Cents(702.0).per_volt_octave()), Cents(702.0));
+    }
+
+    #[test] fn classify_comma() {
+        let pythagorean = Cents::from_ratio(1.5) * 12.0 - Cents(1200.0 * 7.0);
+        assert_eq!(pythagorean.classify_comma(Cents(0.1)), Some("Pythagorean comma"));
+        assert_eq!((Cents(0.0) - pythagorean).classify_comma(Cents(0.1)), Some("Pythagorean comma"));
+        assert_eq!(Cents::from_ratio(81.0 / 80.0).classify_comma(Cents(0.1)), Some("syntonic comma"));
+        assert_eq!(Cents::from_ratio(128.0 / 125.0).classify_comma(Cents(0.1)), Some("diesis"));
+        assert_eq!((pythagorean - Cents::from_ratio(81.0 / 80.0)).classify_comma(Cents(0.1)), Some("schisma"));
+
+        // Both syntonic and Pythagorean commas are within tolerance, the closest wins
+        assert_eq!(Cents(22.0).classify_comma(Cents(2.0)), Some("syntonic comma"));
+        assert_eq!(Cents(10.0).classify_comma(Cents(1.0)), None);
+    }
+
+    #[test] fn sub_clamped() {
+        assert_eq!(Hz(55.0).sub_clamped(Cents(10000.0), Hz(20.0)), Hz(20.0));
+        assert_eq!(Hz(55.0).sub_clamped(Cents(1200.0), Hz(20.0)), Hz(27.5));
+        assert_eq!(Hz(55.0).sub_clamped(Cents(-1200.0), Hz(20.0)), Hz(110.0));
+    }
+
+    #[test] fn in_tune() {
+        assert!(Hz(442.0).in_tune(Hz(440.0), Cents(10.0)));
+        assert!(!Hz(442.0).in_tune(Hz(440.0), Cents(5.0)));
+        assert!(Hz(438.0).in_tune(Hz(440.0), Cents(10.0)));
+        assert!(!Hz(f32::NAN).in_tune(Hz(440.0), Cents(10.0)));
+
+        assert!((Hz(442.0).deviation(Hz(440.0)).0 - 7.85).abs() < 0.01);
+        assert!(Hz(438.0).deviation(Hz(440.0)).0 < 0.0);
+    }
+
+    #[test] fn cents_key() {
+        assert_eq!(Cents(701.955).to_key(1.0), Cents(702.4).to_key(1.0));
+        assert_ne!(Cents(701.955).to_key(1.0), Cents(702.6).to_key(1.0));
+        assert_eq!(Cents(-13.7).to_key(5.0), CentsKey(-3));
+        assert_eq!(Cents(701.955).to_key(0.5).to_cents(0.5), Cents(702.0));
+
+        let mut classes = ::std::collections::HashMap::new();
+        for &c in &[Cents(700.0), Cents(701.955), Cents(386.3), Cents(699.0)] {
+            *classes.entry(c.to_key(10.0)).or_insert(0) += 1;
+        }
+        assert_eq!(classes[&CentsKey(70)], 3);
+        assert_eq!(classes[&Cents(390.0).to_key(10.0)], 1);
+    }
+
+    #[test] fn nearest_unison_offset() {
+        assert_eq!(Cents(1190.0).nearest_unison_offset(), Cents(-10.0));
+        assert_eq!(Cents(10.0).nearest_unison_offset(), Cents(10.0));
+        assert_eq!(Cents(610.0).nearest_unison_offset(), Cents(-590.0));
+        assert_eq!(Cents(700.0).nearest_unison_offset(), Cents(-500.0));
+        assert_eq!(Cents(600.0).nearest_unison_offset(), Cents(600.0));
+        assert_eq!(Cents(-600.0).nearest_unison_offset(), Cents(600.0));
+        assert_eq!(Cents(-2410.0).nearest_unison_offset(), Cents(-10.0));
+    }
+
+    #[test] fn try_from_ratio() {
+        assert_eq!(Cents::try_from_ratio(2.0), Ok(Cents(1200.0)));
+        assert_eq!(Cents::try_from_ratio(0.0), Err(RatioError::NonPositive));
+        assert_eq!(Cents::try_from_ratio(-1.5), Err(RatioError::NonPositive));
+        assert_eq!(Cents::try_from_ratio(f32::NAN), Err(RatioError::NotFinite));
+        assert_eq!(Cents::try_from_ratio(f32::INFINITY), Err(RatioError::NotFinite));
+    }
+
+    #[test] fn named_interval_to() {
+        let (ratio, error) = Hz(550.0).named_interval_to(Hz(440.0), 8);
+        assert_eq!(ratio, (5, 4));
+        assert!(error.0.abs() < 0.001);
+
+        let (ratio, error) = Hz(440.0).named_interval_to(Hz(550.0), 8);
+        assert_eq!(ratio, (4, 5));
+        assert!(error.0.abs() < 0.001);
+
+        let (ratio, error) = (Hz(440.0) + Cents(700.0)).named_interval_to(Hz(440.0), 8);
+        assert_eq!(ratio, (3, 2));
+        assert!((error.0 + 1.955).abs() < 0.01);
+
+        for &(a, b) in &[(440.0, 0.0), (0.0, 440.0), (440.0, -440.0), (f32::INFINITY, 440.0), (440.0, f32::NAN)] {
+            let (ratio, error) = Hz(a).named_interval_to(Hz(b), 8);
+            assert_eq!(ratio, (1, 1));
+            assert!(error.0.is_nan());
+        }
+    }
 }
@@ -1,6 +1,20 @@
-use super::{Hz, Cents};
+use super::{Hz, Cents, Ratio, gcd};
 
+use std::collections::HashMap;
 use std::iter::IntoIterator;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod beating;
+pub mod chords;
+pub mod csv;
+pub mod kbm;
+pub mod lattice;
+pub mod mts;
+pub mod optimize;
+pub mod scaleworkshop;
+pub mod scl;
 
 const A440: Hz = Hz(440.0);
 
@@ -15,31 +29,288 @@ pub trait Tuning {
     /// Returns an interval from one step to another
     fn interval(&self, from: i32, to: i32) -> Option<Cents> {
         match (self.pitch(from), self.pitch(to)) {
-            (Some(to), Some(from)) => Some(to / from),
+            (Some(from), Some(to)) => Some(to / from),
             _ => None,
         }
     }
+    /// Returns the pitch between two adjacent steps, interpolated geometrically
+    ///
+    /// Integer steps give the same pitch as `pitch`, `0.5` gives the middle of steps 0 and 1
+    /// in cents.
+    fn pitch_fractional(&self, step: f32) -> Option<Hz> {
+        let lo = step.floor();
+        if !lo.is_finite() || lo < i32::MIN as f32 || lo >= i32::MAX as f32 { return None }
+
+        let low = self.pitch(lo as i32)?;
+        if step == lo { return Some(low) }
+
+        let high = self.pitch(lo as i32 + 1)?;
+        Some(low + (high / low) * (step - lo))
+    }
+    /// Returns the interval from the reference pitch to a step
+    fn pitch_cents(&self, step: i32) -> Option<Cents> {
+        self.pitch(step).map(|hz| hz / self.reference_pitch())
+    }
+    /// Returns the first `count` harmonics of a step, starting with its pitch
+    fn partials(&self, step: i32, count: u32) -> Option<Vec<Hz>> {
+        let fundamental = self.pitch(step)?;
+        Some((1..count + 1).map(|k| fundamental * k as f32).collect())
+    }
+    /// Returns the interval at which the tuning repeats, if any. It's the octave by default.
+    fn equave(&self) -> Option<Cents> {
+        Some(Cents(1200.0))
+    }
+    /// Returns the number of steps in one equave, if the tuning repeats in steps
+    fn steps_per_equave(&self) -> Option<usize> {
+        None
+    }
+    /// Splits a step into the number of equaves and the degree within the equave
+    ///
+    /// Degrees are always non-negative, so step `-1` is the last degree one equave down.
+    fn decompose_step(&self, step: i32) -> Option<(i32, i32)> {
+        self.equave()?;
+        let len = self.steps_per_equave()? as i32;
+        if len == 0 { return None }
+
+        Some((step.div_euclid(len), step.rem_euclid(len)))
+    }
+    /// Returns the steps of the range along with their pitches and intervals from step 0
+    ///
+    /// The three vectors are parallel, which is handy for plotting.
+    /// Returns `None` if any step is unmapped.
+    fn sample(&self, range: RangeInclusive<i32>) -> Option<(Vec<i32>, Vec<Hz>, Vec<Cents>)> {
+        let steps: Vec<i32> = range.collect();
+        let pitches = steps.iter().map(|&s| self.pitch(s)).collect::<Option<Vec<_>>>()?;
+        let cents = steps.iter().map(|&s| self.interval(0, s)).collect::<Option<Vec<_>>>()?;
+
+        Some((steps, pitches, cents))
+    }
+    /// Flips the direction of steps around the reference pitch
+    fn descending(self) -> Descending<Self> where Self: Sized {
+        Descending(self)
+    }
+    /// Walks from `start` by melodic intervals given in steps, collecting the pitches
+    ///
+    /// The first pitch is the one of `start`, followed by one pitch per delta.
+    fn walk_steps(&self, start: i32, deltas: &[i32]) -> Vec<Option<Hz>> {
+        let steps = ::std::iter::once(start).chain(deltas.iter().scan(start, |step, &delta| {
+            *step = step.saturating_add(delta);
+            Some(*step)
+        }));
+        steps.map(|s| self.pitch(s)).collect()
+    }
+    /// Borrows the tuning with its intervals kept, but moved to a new reference pitch
+    fn rereference(&self, new_reference: Hz) -> ReReferenced<'_, Self> where Self: Sized {
+        ReReferenced { inner: self, reference: new_reference }
+    }
+    /// Returns the steps of `search` whose pitches lie in `[lo, hi]`
+    fn steps_in_range(&self, lo: Hz, hi: Hz, search: RangeInclusive<i32>) -> Vec<i32> {
+        search
+            .filter(|&s| self.pitch(s).is_some_and(|hz| hz.0 >= lo.0 && hz.0 <= hi.0))
+            .collect()
+    }
+    /// Finds the step of `search` closest to `pitch`
+    ///
+    /// Returns the step and the interval from its pitch to `pitch`, or `None` if no step
+    /// of `search` is mapped.
+    fn quantize(&self, pitch: Hz, search: RangeInclusive<i32>) -> Option<(i32, Cents)> {
+        let table: Vec<(i32, Hz)> = search.filter_map(|s| self.pitch(s).map(|hz| (s, hz))).collect();
+        nearest_step(&table, pitch)
+    }
+    /// Finds the step of `search` closest to `ratio` above the reference pitch
+    ///
+    /// The error is the interval from the step to the ratio, like in `quantize`.
+    fn step_for_ratio(&self, ratio: f32, search: RangeInclusive<i32>) -> Option<(i32, Cents)> {
+        self.quantize(self.reference_pitch() + Cents::from_ratio(ratio), search)
+    }
+    /// Quantizes every pitch like `quantize`, computing the pitches of `search` only once
+    fn quantize_many(&self, pitches: &[Hz], search: RangeInclusive<i32>) -> Vec<Option<(i32, Cents)>> {
+        let table: Vec<(i32, Hz)> = search.filter_map(|s| self.pitch(s).map(|hz| (s, hz))).collect();
+        pitches.iter().map(|&pitch| nearest_step(&table, pitch)).collect()
+    }
+    /// Returns intervals from step 0 to steps `0..count`, stopping at the first unmapped step
+    fn cents_table(&self, count: usize) -> Vec<Cents> {
+        (0..count as i32)
+            .map_while(|i| self.interval(0, i))
+            .collect()
+    }
 }
 
 /// Equal division of 2:1
+///
+/// Other equaves, like 3:1 for Bohlen-Pierce, can be divided with `Edo::with_equave`.
 #[derive(Debug, Clone)]
 pub struct Edo {
     cardinality: u16,
+    equave: Cents,
     reference: Hz,
 }
 
 impl Edo {
     /// Creates a new EDO with given cardinality and reference pitch
     pub fn new(cardinality: u16, reference: Hz) -> Self {
+        Self::with_equave(cardinality, Cents(1200.0), reference)
+    }
+
+    /// Quarter tones, 24 EDO
+    pub fn quarter_tone(reference: Hz) -> Self {
+        Self::new(24, reference)
+    }
+
+    /// Sixth tones, 36 EDO
+    pub fn sixth_tone(reference: Hz) -> Self {
+        Self::new(36, reference)
+    }
+
+    /// Eighth tones, 48 EDO
+    pub fn eighth_tone(reference: Hz) -> Self {
+        Self::new(48, reference)
+    }
+
+    /// Creates an equal division of an arbitrary equave
+    pub fn with_equave(cardinality: u16, equave: Cents, reference: Hz) -> Self {
         Edo {
-            cardinality, reference
+            cardinality, equave, reference
         }
     }
 
+    /// Parses a specification like `"31edo"`, `"12ed2"` or `"13ed3"`
+    ///
+    /// The equave after `ed` is a frequency ratio, either a number or a fraction,
+    /// so `"7ed3/2"` and `"12ed2.0"` work too. Letter case doesn't matter.
+    pub fn from_str_spec(s: &str, reference: Hz) -> Result<Self, EdoParseError> {
+        let s = s.trim();
+        // ASCII lowercasing keeps the byte positions
+        let pos = s.to_ascii_lowercase().find("ed").ok_or_else(|| EdoParseError::Malformed(s.into()))?;
+        let (card, equave) = (&s[..pos], &s[pos + 2..]);
+
+        let card: u16 = card.parse()
+            .map_err(|_| EdoParseError::InvalidCardinality(card.into()))?;
+        if card == 0 { return Err(EdoParseError::InvalidCardinality("0".into())) }
+
+        let ratio = if equave.eq_ignore_ascii_case("o") {
+            Some(2.0)
+        } else if let Some(pos) = equave.find('/') {
+            match (equave[..pos].parse::<f32>(), equave[pos + 1..].parse::<f32>()) {
+                (Ok(n), Ok(d)) => Some(n / d),
+                _ => None,
+            }
+        } else {
+            equave.parse::<f32>().ok()
+        };
+        let equave = ratio
+            .filter(|r| r.is_finite() && *r > 1.0)
+            .map(Cents::from_ratio)
+            .ok_or_else(|| EdoParseError::InvalidEquave(equave.into()))?;
+
+        Ok(Self::with_equave(card, equave, reference))
+    }
+
     /// Creates a new EDO with given cardinality and `Hz(440.0)` as reference pitch
     pub fn new_a440(cardinality: u16) -> Self {
         Self::new(cardinality, A440)
     }
+
+    /// Returns the reference pitch
+    pub fn reference(&self) -> Hz {
+        self.reference
+    }
+
+    /// Changes the reference pitch, moving every step along with it
+    pub fn set_reference(&mut self, reference: Hz) {
+        self.reference = reference;
+    }
+
+    /// Makes a scale of the EDO from the number of EDO steps between its degrees
+    ///
+    /// For example, `[2, 2, 1, 2, 2, 2, 1]` is the major scale in 12 EDO.
+    /// Panics if the pattern doesn't add up to the cardinality.
+    pub fn named_scale(&self, pattern: &[u32]) -> CyclicTuning {
+        let total: u32 = pattern.iter().sum();
+        assert_eq!(total, u32::from(self.cardinality), "the pattern doesn't span the equave");
+
+        let step = self.step_size();
+        let steps: Vec<Cents> = pattern.iter()
+            .scan(0, |acc, &n| { *acc += n; Some(step * *acc as f32) })
+            .collect();
+
+        CyclicTuning::from_cents(&steps, self.reference)
+    }
+
+    /// Makes the maximally even scale of `notes` notes in the EDO
+    ///
+    /// Degree `i` is `floor(i * cardinality / notes)` steps of the EDO.
+    /// Panics if `notes` is zero or more than the cardinality.
+    pub fn maximally_even(&self, notes: usize) -> CyclicTuning {
+        let card = usize::from(self.cardinality);
+        assert!(notes > 0 && notes <= card);
+
+        let pattern: Vec<u32> = (1..notes + 1)
+            .map(|i| (i * card / notes - (i - 1) * card / notes) as u32)
+            .collect();
+        self.named_scale(&pattern)
+    }
+
+    /// Returns the signed error of the closest step to each harmonic from 1 to `up_to`
+    ///
+    /// A positive error means the EDO step is sharp of the harmonic.
+    pub fn harmonic_errors(&self, up_to: u32) -> Vec<(u32, Cents)> {
+        let step = self.step_size();
+        (1..up_to + 1).map(|n| {
+            let target = Cents::from_ratio(n as f32);
+            let steps = (target.0 / step.0).round();
+            (n, step * steps - target)
+        }).collect()
+    }
+
+    /// Returns the steps of `range` within `tolerance` of `ratio` or any of its transpositions
+    /// by whole equaves
+    pub fn steps_approximating(&self, ratio: f32, tolerance: Cents, range: RangeInclusive<i32>) -> Vec<i32> {
+        let target = Cents::from_ratio(ratio);
+        let step = self.step_size();
+        let equave = self.equave.0;
+
+        range.filter(|&s| {
+            let error = (step * s as f32 - target).0.rem_euclid(equave);
+            error.min(equave - error) <= tolerance.0
+        }).collect()
+    }
+
+    /// Returns the degree of a step within the equave, `0..cardinality`
+    pub fn enharmonic_class(&self, step: i32) -> u16 {
+        step.rem_euclid(i32::from(self.cardinality)) as u16
+    }
+
+    /// Checks whether two steps are the same degree in different equaves
+    pub fn are_enharmonic(&self, step_a: i32, step_b: i32) -> bool {
+        self.enharmonic_class(step_a) == self.enharmonic_class(step_b)
+    }
+
+    /// Returns the nearest step of the EDO to every pitch of a melody
+    pub fn transcribe(&self, melody: &[Hz]) -> Vec<i32> {
+        let step = self.step_size();
+        melody.iter()
+            .map(|&hz| ((hz / self.reference).0 / step.0).round() as i32)
+            .collect()
+    }
+
+    /// Compares how two EDOs approximate harmonics from 1 to `harmonics`
+    ///
+    /// Returns the RMS of differences in cents between the closest steps of both EDOs to each
+    /// harmonic, so a lower value means more similar EDOs and `0.0` means identical approximations.
+    pub fn similarity(&self, other: &Edo, harmonics: u32) -> f32 {
+        if harmonics == 0 { return 0.0 }
+
+        let sum: f32 = self.harmonic_errors(harmonics).iter()
+            .zip(other.harmonic_errors(harmonics))
+            .map(|(&(_, a), (_, b))| (a - b).0 * (a - b).0)
+            .sum();
+        (sum / harmonics as f32).sqrt()
+    }
+
+    fn step_size(&self) -> Cents {
+        Cents(self.equave.0 / f32::from(self.cardinality))
+    }
 }
 
 impl Tuning for Edo {
@@ -48,14 +319,81 @@ impl Tuning for Edo {
     }
 
     fn pitch(&self, step: i32) -> Option<Hz> {
-        let int = Cents(1200.0 / f32::from(self.cardinality)) * step as f32;
-        Some(self.reference + int)
+        Some(self.reference + self.step_size() * step as f32)
     }
 
     fn interval(&self, from: i32, to: i32) -> Option<Cents> {
         let delta = (to - from) as f32;
-        Some(Cents(1200.0 / f32::from(self.cardinality) * delta))
+        Some(self.step_size() * delta)
+    }
+
+    fn equave(&self) -> Option<Cents> {
+        Some(self.equave)
+    }
+
+    fn steps_per_equave(&self) -> Option<usize> {
+        Some(usize::from(self.cardinality))
+    }
+}
+
+/// An error that occured while parsing an EDO specification
+#[derive(Debug, Clone, PartialEq)]
+pub enum EdoParseError {
+    /// The specification has no `ed` in it
+    Malformed(String),
+    /// The number of divisions is not a positive integer
+    InvalidCardinality(String),
+    /// The divided interval is not a positive interval
+    InvalidEquave(String),
+}
+
+impl ::std::fmt::Display for EdoParseError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            EdoParseError::Malformed(ref s) => write!(f, "not an EDO specification: {}", s),
+            EdoParseError::InvalidCardinality(ref s) => write!(f, "invalid number of divisions: {}", s),
+            EdoParseError::InvalidEquave(ref s) => write!(f, "invalid equave: {}", s),
+        }
+    }
+}
+
+impl ::std::error::Error for EdoParseError {}
+
+fn nearest_step(table: &[(i32, Hz)], pitch: Hz) -> Option<(i32, Cents)> {
+    table.iter()
+        .map(|&(step, hz)| (step, pitch / hz))
+        .min_by(|a, b| a.1.abs().0.partial_cmp(&b.1.abs().0).unwrap_or(::std::cmp::Ordering::Equal))
+}
+
+/// Stacks `count` generators upwards from the unison and reduces them into the period
+///
+/// The result is sorted and starts with `Cents(0.0)`, the period itself is not included.
+pub fn stack(generator: Cents, period: Cents, count: usize) -> Vec<Cents> {
+    let mut degrees: Vec<Cents> = (0..count)
+        .map(|k| Cents((generator.0 * k as f32).rem_euclid(period.0)))
+        .collect();
+    degrees.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+    degrees
+}
+
+/// Guesses the EDO a set of intervals belongs to
+///
+/// Returns the smallest cardinality for which every interval is within `tolerance`
+/// of some EDO step. Cardinalities are tried up to 1200 and while the step is
+/// larger than twice the tolerance, since smaller steps would match anything.
+pub fn detect_edo(cents: &[Cents], tolerance: Cents) -> Option<u16> {
+    for card in 1..1201_u16 {
+        let step = 1200.0 / f32::from(card);
+        if step <= 2.0 * tolerance.0 { break }
+
+        let fits = cents.iter().all(|c| {
+            let steps = (c.0 / step).round();
+            (c.0 - steps * step).abs() <= tolerance.0
+        });
+        if fits { return Some(card) }
     }
+
+    None
 }
 
 #[derive(Debug, Clone)]
@@ -77,6 +415,43 @@ impl EqualSteps {
     pub fn new_a440(step: Cents) -> Self {
         Self::new(step, A440)
     }
+
+    /// Creates an equal division of the octave into a possibly fractional number of steps
+    ///
+    /// For example, 12.5 gives steps of 96 cents, reaching the octave every 25 steps.
+    pub fn from_edo_float(divisions: f32, reference: Hz) -> Self {
+        Self::new(Cents(1200.0 / divisions), reference)
+    }
+
+    /// Wendy Carlos' Alpha scale, 78.0 cents per step
+    pub fn carlos_alpha(reference: Hz) -> Self {
+        Self::new(Cents(78.0), reference)
+    }
+
+    /// Wendy Carlos' Beta scale, 63.8 cents per step
+    pub fn carlos_beta(reference: Hz) -> Self {
+        Self::new(Cents(63.8), reference)
+    }
+
+    /// Wendy Carlos' Gamma scale, 35.1 cents per step
+    pub fn carlos_gamma(reference: Hz) -> Self {
+        Self::new(Cents(35.1), reference)
+    }
+
+    /// Returns the step size
+    pub fn step(&self) -> Cents {
+        self.step
+    }
+
+    /// Returns the reference pitch
+    pub fn reference(&self) -> Hz {
+        self.reference
+    }
+
+    /// Changes the reference pitch, moving every step along with it
+    pub fn set_reference(&mut self, reference: Hz) {
+        self.reference = reference;
+    }
 }
 
 impl Tuning for EqualSteps {
@@ -88,6 +463,166 @@ impl Tuning for EqualSteps {
         let int = self.step * step as f32;
         Some(self.reference + int)
     }
+
+    fn equave(&self) -> Option<Cents> {
+        None
+    }
+}
+
+/// A rank-2 temperament: a scale made by stacking a generator and reducing it by a period
+///
+/// For example, an octave period with a 696.6 cents generator and 7 generators per period
+/// gives the diatonic scale of quarter-comma meantone.
+#[derive(Debug, Clone)]
+pub struct RankTwo {
+    period: Cents,
+    generator: Cents,
+    scale: CyclicTuning,
+}
+
+impl RankTwo {
+    /// Creates a scale of `generators_per_period` notes made of generators stacked
+    /// upwards from the reference pitch
+    pub fn new(period: Cents, generator: Cents, generators_per_period: usize, reference: Hz) -> Self {
+        assert!(generators_per_period > 0);
+
+        let mut degrees = stack(generator, period, generators_per_period);
+        degrees.remove(0);
+        degrees.push(period);
+
+        RankTwo {
+            period, generator,
+            scale: CyclicTuning::from_cents(&degrees, reference),
+        }
+    }
+
+    pub fn period(&self) -> Cents {
+        self.period
+    }
+
+    pub fn generator(&self) -> Cents {
+        self.generator
+    }
+}
+
+impl Tuning for RankTwo {
+    fn reference_pitch(&self) -> Hz {
+        self.scale.reference
+    }
+
+    fn pitch(&self, step: i32) -> Option<Hz> {
+        self.scale.pitch(step)
+    }
+
+    fn equave(&self) -> Option<Cents> {
+        Some(self.period)
+    }
+
+    fn steps_per_equave(&self) -> Option<usize> {
+        self.scale.steps_per_equave()
+    }
+}
+
+/// A tuning with steps going down, see `Tuning::descending`
+///
+/// Step `n` of the wrapper is step `-n` of the inner tuning.
+#[derive(Debug, Clone)]
+pub struct Descending<T>(T);
+
+impl<T> Descending<T> {
+    /// Returns the ascending tuning back
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Tuning> Tuning for Descending<T> {
+    fn reference_pitch(&self) -> Hz {
+        self.0.reference_pitch()
+    }
+
+    fn pitch(&self, step: i32) -> Option<Hz> {
+        self.0.pitch(step.checked_neg()?)
+    }
+
+    fn equave(&self) -> Option<Cents> {
+        self.0.equave()
+    }
+
+    fn steps_per_equave(&self) -> Option<usize> {
+        self.0.steps_per_equave()
+    }
+}
+
+/// A tuning moved to another reference pitch, see `Tuning::rereference`
+#[derive(Debug, Clone)]
+pub struct ReReferenced<'a, T: 'a> {
+    inner: &'a T,
+    reference: Hz,
+}
+
+impl<'a, T: Tuning> Tuning for ReReferenced<'a, T> {
+    fn reference_pitch(&self) -> Hz {
+        self.reference
+    }
+
+    fn pitch(&self, step: i32) -> Option<Hz> {
+        self.inner.interval(0, step).map(|int| self.reference + int)
+    }
+
+    fn equave(&self) -> Option<Cents> {
+        self.inner.equave()
+    }
+
+    fn steps_per_equave(&self) -> Option<usize> {
+        self.inner.steps_per_equave()
+    }
+}
+
+/// A tuning with some of its steps detuned by a few cents, like a table of measured corrections
+#[derive(Debug, Clone)]
+pub struct OffsetTuning<T> {
+    inner: T,
+    offsets: HashMap<i32, Cents>,
+}
+
+impl<T: Tuning> OffsetTuning<T> {
+    /// Wraps `inner`, moving every step found in `offsets` by its interval
+    ///
+    /// Steps without an offset keep their pitch, and so does step 0, so the reference pitch
+    /// stays the same.
+    pub fn new(inner: T, offsets: HashMap<i32, Cents>) -> Self {
+        OffsetTuning {
+            inner, offsets
+        }
+    }
+
+    /// Returns the tuning without offsets back
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Tuning> Tuning for OffsetTuning<T> {
+    fn reference_pitch(&self) -> Hz {
+        self.inner.reference_pitch()
+    }
+
+    fn pitch(&self, step: i32) -> Option<Hz> {
+        let hz = self.inner.pitch(step)?;
+        Some(match self.offsets.get(&step) {
+            Some(&offset) if step != 0 => hz + offset,
+            _ => hz,
+        })
+    }
+
+    fn equave(&self) -> Option<Cents> {
+        self.inner.equave()
+    }
+
+    fn steps_per_equave(&self) -> Option<usize> {
+        self.inner.steps_per_equave()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -110,8 +645,12 @@ impl Tuning for EqualSteps {
 /// println!("{:?}", tuning.pitch(3).unwrap()); // => Cents(880.0)
 /// println!("{:?}", tuning.pitch(4).unwrap()); // => Cents(1100.0)
 /// ```
+///
+/// A tuning of the period alone (see `CyclicTuning::repeat`) has step `n` exactly `n` periods
+/// away from the reference, and an empty tuning maps every step to the reference.
 pub struct CyclicTuning {
     steps: Vec<Cents>,
+    ratios: Option<Vec<Ratio>>,
     reference: Hz,
 }
 
@@ -122,7 +661,7 @@ impl CyclicTuning {
     pub fn from_cents<'a, I: IntoIterator<Item=&'a Cents>>(steps: I, reference: Hz) -> Self {
         let steps = steps.into_iter().cloned().collect();
         CyclicTuning {
-            steps, reference
+            steps, reference, ratios: None
         }
     }
 
@@ -132,82 +671,698 @@ impl CyclicTuning {
     pub fn from_ratios<'a, I: IntoIterator<Item=&'a f32>>(steps: I, reference: Hz) -> Self {
         let steps = steps.into_iter().map(|r| Cents::from_ratio(*r)).collect();
         CyclicTuning {
-            steps, reference
+            steps, reference, ratios: None
         }
     }
-}
 
-impl Tuning for CyclicTuning {
-    fn reference_pitch(&self) -> Hz {
-        self.reference
+    /// Create a cyclic tuning from exact ratios
+    ///
+    /// Unlike `from_ratios`, the tuning remembers the ratios, so exporters can write them exactly.
+    /// `Ratio::new(1, 1)` should be excluded and the last interval is the period.
+    pub fn from_exact_ratios<'a, I: IntoIterator<Item=&'a Ratio>>(steps: I, reference: Hz) -> Self {
+        let ratios: Vec<Ratio> = steps.into_iter().cloned().collect();
+        let steps = ratios.iter().map(|r| r.to_cents()).collect();
+        CyclicTuning {
+            steps, reference, ratios: Some(ratios)
+        }
     }
 
-    fn pitch(&self, step: i32) -> Option<Hz> {
-        if self.steps.is_empty() {
-            return Some(self.reference)
+    /// Create a cyclic tuning from cents like `from_cents`, with `ref_step` tuned to `reference`
+    ///
+    /// The pitch of step 0 is then `reference` lowered by the interval from step 0 to `ref_step`.
+    pub fn with_reference_at<'a, I: IntoIterator<Item=&'a Cents>>(steps: I, reference: Hz, ref_step: i32) -> Self {
+        let mut tuning = Self::from_cents(steps, reference);
+        if let Some(offset) = tuning.interval(0, ref_step) {
+            tuning.reference = reference - offset;
         }
+        tuning
+    }
 
-        let len = self.steps.len() as i32;
-        let last = *self.steps.last().unwrap();
-        let (rem, div) = (step % len, step / len);
-
-        let hz = if rem == 0 {
-            self.reference + last * div as f32
-        } else {
-            let step =
-                if rem > 0 { self.steps[(rem - 1) as usize] }
-                else { self.steps[(len + rem - 1) as usize] - last };
-            self.reference + (last * div as f32 + step)
-        };
+    /// Create a cyclic tuning of a single interval repeated both ways
+    ///
+    /// The interval is the only step and the period. For example, `Cents(1200.0)` gives octaves.
+    pub fn repeat(interval: Cents, reference: Hz) -> Self {
+        Self::from_cents(&[interval], reference)
+    }
 
-        Some(hz)
+    /// Returns the exact ratios of the steps, if the tuning was built from them
+    pub fn ratios(&self) -> Option<&[Ratio]> {
+        self.ratios.as_ref().map(|r| &r[..])
     }
-}
 
-#[derive(Debug, Clone)]
-/// A map from MIDI notes to pitches
-/// 
-/// This is what your synth should actually use
-pub struct MidiTuning {
-    pitches: Vec<Hz>,
-}
+    /// Returns the degrees as cents above the reference, the last one being the period
+    pub fn cumulative_cents(&self) -> Vec<Cents> {
+        self.steps.clone()
+    }
 
-impl MidiTuning {
-    /// Lineary maps the tuning to MIDI steps. `refkey` is a MIDI note of the reference pitch.
-    pub fn from_tuning<T: Tuning>(tuning: T, refkey: u8) -> Option<Self> {
-        assert!(refkey < 127);
+    /// Returns the sizes of steps between adjacent degrees, starting from the reference
+    ///
+    /// The last step goes from the last degree up to the period.
+    pub fn step_sizes(&self) -> Vec<Cents> {
+        let mut prev = Cents(0.0);
+        self.steps.iter().map(|&c| {
+            let size = c - prev;
+            prev = c;
+            size
+        }).collect()
+    }
 
-        let mut pitches = Vec::with_capacity(127);
-        for i in 0..127 {
-            if let Some(hz) = tuning.pitch(i - i32::from(refkey)) {
-                pitches.push(hz)
-            }
-            else {
-                return None
-            }
-        }
+    /// Create a cyclic tuning from measured frequencies of one period
+    ///
+    /// Every frequency, as well as `period`, becomes an interval above `reference`.
+    /// The intervals are sorted, so the period should be the highest frequency.
+    pub fn from_frequencies(freqs: &[Hz], period: Hz, reference: Hz) -> Self {
+        let mut steps: Vec<Cents> = freqs.iter().map(|&hz| hz / reference).collect();
+        steps.push(period / reference);
+        steps.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
 
-        Some(MidiTuning {
-            pitches
-        })
+        Self::from_cents(&steps, reference)
     }
 
-    /// Creates `MidiTuning` from a slice of pitches
-    pub fn from_pitches(hzs: &[Hz]) -> Option<Self> {
-        if hzs.len() < 127 { return None }
+    /// Returns the reference pitch
+    pub fn reference(&self) -> Hz {
+        self.reference
+    }
 
-        let pitches = hzs[0..127].to_owned();
-        Some(MidiTuning {
-            pitches
-        })   
+    /// Changes the reference pitch, moving every step along with it
+    pub fn set_reference(&mut self, reference: Hz) {
+        self.reference = reference;
     }
-}
+
+    /// Returns all the modes of the scale, from the brightest to the darkest
+    ///
+    /// Modes are the rotations of the scale, and brighter modes have a larger
+    /// sum of their degrees above the tonic.
+    pub fn modes_by_brightness(&self) -> Vec<CyclicTuning> {
+        let len = self.steps.len();
+        let period = match self.steps.last() {
+            Some(&p) => p,
+            None => return vec![self.clone()],
+        };
+
+        let degree = |i: usize| {
+            let octaves = Cents(period.0 * (i / len) as f32);
+            match i % len {
+                0 => octaves,
+                j => octaves + self.steps[j - 1],
+            }
+        };
+
+        let mut modes: Vec<(f32, CyclicTuning)> = (0..len).map(|k| {
+            let tonic = degree(k);
+            let steps: Vec<Cents> = (1..len + 1).map(|i| degree(k + i) - tonic).collect();
+            let brightness = steps.iter().map(|c| c.0).sum();
+            (brightness, CyclicTuning::from_cents(&steps, self.reference))
+        }).collect();
+
+        modes.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(::std::cmp::Ordering::Equal));
+        modes.into_iter().map(|(_, mode)| mode).collect()
+    }
+
+    /// Finds the closest ratio within the prime limit for every step, including the period
+    ///
+    /// Ratios are searched up to the denominator `max_den`.
+    pub fn rationalize(&self, prime_limit: u32, max_den: u32) -> Vec<(u32, u32)> {
+        self.steps.iter()
+            .map(|&c| closest_ratio(c, max_den, |n, d| {
+                largest_prime_factor(n) <= prime_limit && largest_prime_factor(d) <= prime_limit
+            }))
+            .collect()
+    }
+
+    /// Returns the pitches of steps from 0 up to `periods` periods above the reference
+    ///
+    /// That's `periods * len + 1` pitches, unless some steps have no pitch (like in `into_iter`).
+    pub fn expand(&self, periods: usize) -> Vec<Hz> {
+        let last = (periods * self.steps.len()) as i32;
+        (0..last + 1).filter_map(|step| self.pitch(step)).collect()
+    }
+
+    /// Returns the primes used by the exact ratios of the steps, in ascending order
+    ///
+    /// Returns `None` if the tuning doesn't remember its ratios (see `from_exact_ratios`).
+    pub fn primes(&self) -> Option<Vec<u32>> {
+        let ratios = self.ratios.as_ref()?;
+        let mut primes: Vec<u32> = ratios.iter()
+            .flat_map(|r| prime_factors(r.numer()).into_iter().chain(prime_factors(r.denom())))
+            .collect();
+        primes.sort();
+        primes.dedup();
+        Some(primes)
+    }
+
+    /// Ranks dyads of the scale by consonance, most consonant first
+    ///
+    /// Degrees go from `0` (the reference) to the number of steps (the period). The interval
+    /// of every pair `(i, j)` with `i < j` is approximated by the closest ratio `n/d` with both
+    /// terms up to `limit`, and scored by the inverse Tenney height `1 / log2(n * d)`.
+    /// Dyads approximated by 1/1, like comma-sized steps, have no finite score and are left out.
+    pub fn dyad_consonance(&self, limit: u32) -> Vec<((usize, usize), f32)> {
+        let mut degrees = vec![Cents(0.0)];
+        degrees.extend(self.steps.iter().cloned());
+
+        let mut dyads = Vec::new();
+        for i in 0..degrees.len() {
+            for j in i + 1..degrees.len() {
+                let (n, d) = closest_ratio(degrees[j] - degrees[i], limit, |n, _| n <= limit);
+                if n == d { continue }
+
+                let height = (f64::from(n) * f64::from(d)).log2() as f32;
+                dyads.push(((i, j), 1.0 / height));
+            }
+        }
+
+        dyads.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(::std::cmp::Ordering::Equal));
+        dyads
+    }
+
+    /// Counts the intervals between all pairs of degrees within one period
+    ///
+    /// Every ordered pair of distinct degrees gives an interval reduced into the period, so
+    /// each interval is counted along with its inversion. Intervals are put into `bin_cents`
+    /// wide bins centered at multiples of `bin_cents`, and the non-empty bins are returned
+    /// as `(center, count)` in ascending order.
+    pub fn interval_histogram(&self, bin_cents: f32) -> Vec<(f32, u32)> {
+        let period = match self.steps.last() {
+            Some(p) => p.0,
+            None => return Vec::new(),
+        };
+
+        let mut degrees = vec![0.0];
+        degrees.extend(self.steps[..self.steps.len() - 1].iter().map(|c| c.0));
+
+        let mut bins = ::std::collections::BTreeMap::new();
+        for (i, a) in degrees.iter().enumerate() {
+            for (j, b) in degrees.iter().enumerate() {
+                if i == j { continue }
+
+                let interval = (b - a).rem_euclid(period);
+                let bin = (interval / bin_cents).round() as i64;
+                *bins.entry(bin).or_insert(0) += 1;
+            }
+        }
+
+        bins.into_iter().map(|(bin, count)| (bin as f32 * bin_cents, count)).collect()
+    }
+
+    /// Snaps every step to the nearest step of `edo`
+    pub fn snap_to_edo(&self, edo: &Edo) -> CyclicTuning {
+        let step = edo.step_size();
+        let steps: Vec<Cents> = self.steps.iter()
+            .map(|c| step * (c.0 / step.0).round())
+            .collect();
+
+        CyclicTuning::from_cents(&steps, self.reference)
+    }
+
+    /// Returns the total absolute error of `snap_to_edo`
+    pub fn snap_error(&self, edo: &Edo) -> Cents {
+        let snapped = self.snap_to_edo(edo);
+        let error = self.steps.iter().zip(&snapped.steps)
+            .map(|(a, b)| (a.0 - b.0).abs())
+            .sum();
+        Cents(error)
+    }
+
+    /// Combines the degrees of two tunings sharing the same period
+    ///
+    /// Degrees closer than `tolerance` to a previous one are merged into it,
+    /// the reference and the period are taken from `self`.
+    pub fn union(&self, other: &CyclicTuning, tolerance: Cents) -> Result<CyclicTuning, UnionError> {
+        let period = match (self.steps.last(), other.steps.last()) {
+            (Some(&a), Some(&b)) if (a - b).0.abs() <= tolerance.0 => a,
+            _ => return Err(UnionError::PeriodMismatch),
+        };
+
+        let mut degrees: Vec<Cents> = self.steps[..self.steps.len() - 1].iter()
+            .chain(&other.steps[..other.steps.len() - 1])
+            .cloned()
+            .collect();
+        degrees.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(::std::cmp::Ordering::Equal));
+        degrees.dedup_by(|b, a| (b.0 - a.0).abs() <= tolerance.0);
+        degrees.retain(|c| c.0 > tolerance.0 && period.0 - c.0 > tolerance.0);
+        degrees.push(period);
+
+        Ok(CyclicTuning::from_cents(&degrees, self.reference))
+    }
+
+    /// Harry Partch's 43-tone just scale, with a 2/1 period
+    pub fn partch43(reference: Hz) -> Self {
+        const RATIOS: [(u32, u32); 43] = [
+            (81, 80), (33, 32), (21, 20), (16, 15), (12, 11), (11, 10), (10, 9), (9, 8),
+            (8, 7), (7, 6), (32, 27), (6, 5), (11, 9), (5, 4), (14, 11), (9, 7), (21, 16),
+            (4, 3), (27, 20), (11, 8), (7, 5), (10, 7), (16, 11), (40, 27), (3, 2), (32, 21),
+            (14, 9), (11, 7), (8, 5), (18, 11), (5, 3), (27, 16), (12, 7), (7, 4), (16, 9),
+            (9, 5), (20, 11), (11, 6), (15, 8), (40, 21), (64, 33), (160, 81), (2, 1),
+        ];
+
+        let ratios: Vec<Ratio> = RATIOS.iter().map(|&(n, d)| Ratio::new(n, d)).collect();
+        Self::from_exact_ratios(&ratios, reference)
+    }
+}
+
+impl Tuning for CyclicTuning {
+    fn reference_pitch(&self) -> Hz {
+        self.reference
+    }
+
+    fn pitch(&self, step: i32) -> Option<Hz> {
+        if self.steps.is_empty() {
+            return Some(self.reference)
+        }
+
+        let len = self.steps.len() as i32;
+        let last = *self.steps.last().unwrap();
+        let (rem, div) = (step % len, step / len);
+
+        let hz = if rem == 0 {
+            self.reference + last * div as f32
+        } else {
+            let step =
+                if rem > 0 { self.steps[(rem - 1) as usize] }
+                else { self.steps[(len + rem - 1) as usize] - last };
+            self.reference + (last * div as f32 + step)
+        };
+
+        // Bad steps (like a NaN period from a broken import) make bad pitches
+        if hz.0.is_finite() { Some(hz) } else { None }
+    }
+
+    fn equave(&self) -> Option<Cents> {
+        self.steps.last().cloned()
+    }
+
+    fn steps_per_equave(&self) -> Option<usize> {
+        Some(self.steps.len())
+    }
+}
+
+/// Iterates over one period of the tuning as `(step, pitch)`
+///
+/// Steps with non-finite pitches are skipped.
+impl<'a> IntoIterator for &'a CyclicTuning {
+    type Item = (i32, Hz);
+    type IntoIter = CyclicSteps<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        CyclicSteps {
+            tuning: self,
+            step: 0,
+        }
+    }
+}
+
+/// An iterator over one period of a `CyclicTuning`
+#[derive(Debug, Clone)]
+pub struct CyclicSteps<'a> {
+    tuning: &'a CyclicTuning,
+    step: i32,
+}
+
+impl<'a> Iterator for CyclicSteps<'a> {
+    type Item = (i32, Hz);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while (self.step as usize) < self.tuning.steps.len() {
+            let step = self.step;
+            self.step += 1;
+            if let Some(hz) = self.tuning.pitch(step) {
+                return Some((step, hz))
+            }
+        }
+        None
+    }
+}
+
+/// An error that occured while combining two `CyclicTuning`s
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnionError {
+    /// The tunings repeat at different periods
+    PeriodMismatch,
+}
+
+impl ::std::fmt::Display for UnionError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            UnionError::PeriodMismatch => write!(f, "the periods of the tunings differ"),
+        }
+    }
+}
+
+impl ::std::error::Error for UnionError {}
+
+/// An error that occured while building a `CyclicTuning`
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The period was never set
+    NoPeriod,
+    /// Steps (and the period) must be strictly ascending and above the unison
+    NotAscending,
+}
+
+impl ::std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            BuildError::NoPeriod => write!(f, "the period is not set"),
+            BuildError::NotAscending => write!(f, "the steps are not ascending"),
+        }
+    }
+}
+
+impl ::std::error::Error for BuildError {}
+
+/// A step by step builder of `CyclicTuning`
+///
+/// ```rust
+/// # use monochord::{Hz, Cents};
+/// # use monochord::tuning::CyclicTuningBuilder;
+/// let tuning = CyclicTuningBuilder::new()
+///     .reference(Hz(261.63))
+///     .add_ratio(5.0 / 4.0)
+///     .add_cents(Cents(700.0))
+///     .period_cents(Cents(1200.0))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct CyclicTuningBuilder {
+    steps: Vec<Cents>,
+    period: Option<Cents>,
+    reference: Hz,
+}
+
+impl CyclicTuningBuilder {
+    /// Creates an empty builder with `Hz(440.0)` as reference pitch
+    pub fn new() -> Self {
+        CyclicTuningBuilder {
+            steps: Vec::new(),
+            period: None,
+            reference: A440,
+        }
+    }
+
+    /// Sets the reference pitch
+    pub fn reference(self, reference: Hz) -> Self {
+        CyclicTuningBuilder { reference, ..self }
+    }
+
+    /// Adds a step given in cents above the reference
+    pub fn add_cents(mut self, step: Cents) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Adds a step given as a ratio to the reference
+    pub fn add_ratio(self, ratio: f32) -> Self {
+        self.add_cents(Cents::from_ratio(ratio))
+    }
+
+    /// Sets the period
+    pub fn period_cents(self, period: Cents) -> Self {
+        CyclicTuningBuilder { period: Some(period), ..self }
+    }
+
+    /// Builds the tuning, checking that the steps are ascending and the period is set
+    pub fn build(self) -> Result<CyclicTuning, BuildError> {
+        let period = self.period.ok_or(BuildError::NoPeriod)?;
+
+        let mut steps = self.steps;
+        steps.push(period);
+
+        let mut prev = 0.0;
+        for step in &steps {
+            if step.0.is_nan() || step.0 <= prev { return Err(BuildError::NotAscending) }
+            prev = step.0;
+        }
+
+        Ok(CyclicTuning::from_cents(&steps, self.reference))
+    }
+}
+
+impl ::std::default::Default for CyclicTuningBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A map from MIDI notes to pitches
+/// 
+/// This is what your synth should actually use
+pub struct MidiTuning {
+    pitches: Vec<Hz>,
+}
+
+impl MidiTuning {
+    /// Lineary maps the tuning to MIDI steps. `refkey` is a MIDI note of the reference pitch.
+    pub fn from_tuning<T: Tuning>(tuning: T, refkey: u8) -> Option<Self> {
+        assert!(refkey < 128);
+
+        let mut pitches = Vec::with_capacity(128);
+        for i in 0..128 {
+            if let Some(hz) = tuning.pitch(i - i32::from(refkey)) {
+                pitches.push(hz)
+            }
+            else {
+                return None
+            }
+        }
+
+        Some(MidiTuning {
+            pitches
+        })
+    }
+
+    /// Maps the tuning like `from_tuning`, then replaces the pitches of some notes
+    pub fn from_tuning_with_overrides<T: Tuning>(base: T, refkey: u8, overrides: &[(u8, Hz)])
+        -> Result<Self, MidiTuningError>
+    {
+        if refkey > 127 { return Err(MidiTuningError::NoteOutOfRange(refkey)) }
+
+        let mut tuning = Self::from_tuning(base, refkey).ok_or(MidiTuningError::Unmapped)?;
+        let mut overridden = [false; 128];
+        for &(note, hz) in overrides {
+            if note > 127 { return Err(MidiTuningError::NoteOutOfRange(note)) }
+            if overridden[usize::from(note)] { return Err(MidiTuningError::DuplicateNote(note)) }
+
+            overridden[usize::from(note)] = true;
+            tuning.pitches[usize::from(note)] = hz;
+        }
+
+        Ok(tuning)
+    }
+
+    /// Maps the degrees of a scale linearly over the keyboard, with degree 0 on `root_key`
+    ///
+    /// The reference pitch of the scale is replaced by `root_freq`. This is a shortcut for
+    /// the common case of a `.kbm` mapping.
+    pub fn from_scale(scale: &CyclicTuning, root_key: u8, root_freq: Hz) -> Result<Self, MidiTuningError> {
+        if root_key > 127 { return Err(MidiTuningError::NoteOutOfRange(root_key)) }
+
+        let scale = CyclicTuning { reference: root_freq, ..scale.clone() };
+        Self::from_tuning(scale, root_key).ok_or(MidiTuningError::Unmapped)
+    }
+
+    /// Creates `MidiTuning` from a slice of at least 128 pitches, one per MIDI note
+    ///
+    /// Returns `None` for shorter slices. Earlier versions accepted 127 pitches and left
+    /// note 127 out, such slices need the pitch of note 127 appended now.
+    pub fn from_pitches(hzs: &[Hz]) -> Option<Self> {
+        if hzs.len() < 128 { return None }
+
+        let pitches = hzs[0..128].to_owned();
+        Some(MidiTuning {
+            pitches
+        })   
+    }
+
+    /// Creates `MidiTuning` like `from_pitches`, but checks that the pitches make sense
+    ///
+    /// Every one of the first 128 pitches must be positive and not lower than the previous one.
+    pub fn from_pitches_checked(hzs: &[Hz]) -> Result<Self, FromPitchesError> {
+        if hzs.len() < 128 { return Err(FromPitchesError::TooShort(hzs.len())) }
+
+        let pitches = &hzs[0..128];
+        for (note, hz) in pitches.iter().enumerate() {
+            if hz.0.is_nan() || hz.0 <= 0.0 { return Err(FromPitchesError::NonPositive(note as u8)) }
+        }
+        for note in 1..128 {
+            if pitches[note].0 < pitches[note - 1].0 {
+                return Err(FromPitchesError::NotAscending(note as u8))
+            }
+        }
+
+        Ok(MidiTuning {
+            pitches: pitches.to_owned()
+        })
+    }
+
+    /// Creates `MidiTuning` by calling `f` for every MIDI note
+    pub fn from_fn<F: Fn(u8) -> Hz>(f: F) -> Self {
+        let pitches = (0..128).map(f).collect();
+        MidiTuning {
+            pitches
+        }
+    }
+
+    /// Returns, for every note, the interval from the pitch in `other` to the pitch in `self`
+    pub fn diff(&self, other: &MidiTuning) -> [Cents; 128] {
+        let mut deltas = [Cents(0.0); 128];
+        for (n, delta) in deltas.iter_mut().enumerate() {
+            *delta = self.pitches[n] / other.pitches[n];
+        }
+        deltas
+    }
+
+    /// Returns the interval from every note to the next one
+    pub fn step_intervals(&self) -> [Cents; 127] {
+        let mut intervals = [Cents(0.0); 127];
+        for (n, interval) in intervals.iter_mut().enumerate() {
+            *interval = self.pitches[n + 1] / self.pitches[n];
+        }
+        intervals
+    }
+
+    /// Recovers a one-octave scale from the notes starting at `base_note`
+    ///
+    /// Takes the notes from `base_note` up to (but excluding) the first one an octave or more
+    /// above it, and returns their intervals from `base_note` reduced into `[0, 1200)`.
+    /// Like in `pitch_of`, base notes above 127 are clamped to 127.
+    pub fn fold_octave(&self, base_note: u8) -> Vec<Cents> {
+        // Allow for rounding errors in the octave itself
+        const EPSILON: f32 = 0.01;
+
+        let base_note = usize::from(base_note.min(127));
+        let base = self.pitches[base_note];
+        self.pitches[base_note..].iter()
+            .map(|&hz| hz / base)
+            .take_while(|c| c.0 < 1200.0 - EPSILON)
+            .map(|c| c.pitch_class())
+            .collect()
+    }
+
+    /// Returns the pitch of a MIDI note. Notes above 127 are clamped to 127.
+    pub fn pitch_of(&self, note: u8) -> Hz {
+        self.pitches[usize::from(note.min(127))]
+    }
+
+    /// Finds the period of the table in notes, along with its size in cents
+    ///
+    /// A table is periodic if every interval spanning `period` notes is within `tolerance`
+    /// of the same size. Periods of at most 64 notes are tried, so that any period
+    /// repeats at least twice over the keyboard.
+    ///
+    /// An equal tuning repeats after every note, so the octave is preferred: if the table
+    /// repeats at an octave, the smallest such period is returned. Otherwise it's the smallest
+    /// period of any size.
+    pub fn detect_period(&self, tolerance: Cents) -> Option<(usize, Cents)> {
+        let periods: Vec<(usize, Cents)> = (1..self.pitches.len() / 2 + 1)
+            .filter_map(|period| self.period_size(period, tolerance).map(|size| (period, size)))
+            .collect();
+
+        periods.iter()
+            .find(|&&(_, size)| (size.0 - 1200.0).abs() <= tolerance.0)
+            .or_else(|| periods.first())
+            .cloned()
+    }
+
+    /// Moves every pitch `by` notes up (or down, if negative), like a capo
+    ///
+    /// Pitches moved past either end of the keyboard are dropped. The vacated notes continue
+    /// the table with the interval between its two edge notes: shifting up repeats the interval
+    /// between notes 0 and 1 downwards, shifting down repeats the interval between notes
+    /// 126 and 127 upwards.
+    pub fn shift_keys(&self, by: i32) -> MidiTuning {
+        let low_edge = self.pitches[1] / self.pitches[0];
+        let high_edge = self.pitches[127] / self.pitches[126];
+
+        Self::from_fn(|n| {
+            let old = i32::from(n).saturating_sub(by);
+            if old < 0 {
+                self.pitches[0] + low_edge * old as f32
+            }
+            else if old > 127 {
+                self.pitches[127] + high_edge * (old - 127) as f32
+            }
+            else {
+                self.pitches[old as usize]
+            }
+        })
+    }
+
+    /// Returns the pitch of every note divided by `sample_rate`, in cycles per sample
+    pub fn phase_increments(&self, sample_rate: f32) -> [f32; 128] {
+        let mut increments = [0.0; 128];
+        for (inc, hz) in increments.iter_mut().zip(&self.pitches) {
+            *inc = hz.0 / sample_rate;
+        }
+        increments
+    }
+
+    /// Compares the table with `target` mapped like in `from_tuning`
+    ///
+    /// Returns the RMS and the maximum of absolute deviations from the target, in cents.
+    /// Notes the target doesn't map are skipped.
+    pub fn error_stats(&self, target: &dyn Tuning, refkey: u8) -> (Cents, Cents) {
+        let deviations: Vec<f32> = (0..128).filter_map(|n| {
+            let pitch = target.pitch(n - i32::from(refkey))?;
+            Some((self.pitches[n as usize] / pitch).0.abs())
+        }).collect();
+        if deviations.is_empty() { return (Cents(0.0), Cents(0.0)) }
+
+        let sum: f32 = deviations.iter().map(|d| d * d).sum();
+        let max = deviations.iter().cloned().fold(0.0, f32::max);
+        (Cents((sum / deviations.len() as f32).sqrt()), Cents(max))
+    }
+
+    /// Finds the note of the table closest to `hz`, and the pitch bend to play `hz` exactly
+    /// on that note of a 12 EDO synth
+    ///
+    /// `bend_range` is the pitch bend range of the synth in semitones. The bend is
+    /// in `-8192..=8191`, with 0 meaning no bend, and is clamped if `hz` is out of range.
+    pub fn match_frequency(&self, hz: Hz, bend_range: f32) -> (u8, i16) {
+        let note = (0..128u8)
+            .min_by(|&a, &b| {
+                let a = (hz / self.pitches[usize::from(a)]).abs().0;
+                let b = (hz / self.pitches[usize::from(b)]).abs().0;
+                a.partial_cmp(&b).unwrap_or(::std::cmp::Ordering::Equal)
+            })
+            .unwrap();
+
+        let offset = hz / TunedNote { note, offset: Cents(0.0) }.frequency();
+        let bend = (offset.0 / (bend_range * 100.0) * 8192.0).round();
+        let bend = if bend.is_nan() { 0 } else { bend.clamp(-8192.0, 8191.0) as i16 };
+        (note, bend)
+    }
+
+    /// Returns the size of the period if the table repeats every `period` notes
+    fn period_size(&self, period: usize, tolerance: Cents) -> Option<Cents> {
+        let len = self.pitches.len();
+        if period == 0 || period >= len { return None }
+
+        let size = self.pitches[period] / self.pitches[0];
+        let periodic = (0..len - period).all(|n| {
+            let interval = self.pitches[n + period] / self.pitches[n];
+            (interval - size).0.abs() <= tolerance.0
+        });
+        if periodic { Some(size) } else { None }
+    }
+
+    /// Returns the pitches of MIDI notes from `lo` to `hi` inclusive
+    ///
+    /// Panics if `lo` is greater than `hi` or `hi` is greater than 127.
+    pub fn range(&self, lo: u8, hi: u8) -> &[Hz] {
+        assert!(lo <= hi && hi < 128);
+
+        &self.pitches[usize::from(lo)..=usize::from(hi)]
+    }
+}
 
 impl ::std::default::Default for MidiTuning {
     /// Makes 12EDO tuning with A440 at MIDI note 69
     fn default() -> Self {
-        let mut pitches = Vec::with_capacity(127);
-        pitches.extend((0..127).map(|i| A440 + Cents((i - 69) as f32 * 100.0)));
+        let mut pitches = Vec::with_capacity(128);
+        pitches.extend((0..128).map(|i| A440 + Cents((i - 69) as f32 * 100.0)));
 
         MidiTuning {
             pitches
@@ -224,6 +1379,10 @@ impl Tuning for MidiTuning {
         if step < 0 { return None }
         self.pitches.get(step as usize).cloned()
     }
+
+    fn equave(&self) -> Option<Cents> {
+        None
+    }
 }
 
 impl ::std::ops::Index<usize> for MidiTuning {
@@ -233,19 +1392,1129 @@ impl ::std::ops::Index<usize> for MidiTuning {
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test] fn cyclic_tuning() {
-        let tuning = CyclicTuning::from_ratios(&[
-            3.0 / 2.0,
-            2.0_f32
-        ], Hz(440.0));
+/// A MIDI note detuned from its 12 EDO pitch (A440 at note 69), like a note-on with pitch bend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TunedNote {
+    pub note: u8,
+    pub offset: Cents,
+}
 
-        assert_eq!(tuning.pitch(0).unwrap().0, 440.0);
-        assert_eq!(tuning.pitch(1).unwrap().0.round(), 660.0_f32);
+impl TunedNote {
+    /// Returns the pitch of the note
+    pub fn frequency(&self) -> Hz {
+        A440 + Cents((f32::from(self.note) - 69.0) * 100.0) + self.offset
+    }
+
+    /// Finds the nearest MIDI note to a pitch, along with the offset from it
+    ///
+    /// The offset is within ±50 cents, unless the pitch is out of the MIDI range.
+    pub fn from_hz(hz: Hz) -> TunedNote {
+        let semitones = 69.0 + (hz / A440).0 / 100.0;
+        let note = if semitones.is_nan() { 69 } else { semitones.round().clamp(0.0, 127.0) as u8 };
+        let base = TunedNote { note, offset: Cents(0.0) }.frequency();
+        TunedNote { note, offset: hz / base }
+    }
+}
+
+/// Finds the ratio closest to `cents` with a denominator up to `max_den` among accepted ones
+///
+/// `1/1` is always accepted.
+fn closest_ratio<F: Fn(u32, u32) -> bool>(cents: Cents, max_den: u32, accept: F) -> (u32, u32) {
+    let x = f64::from(cents.0) / 1200.0;
+    let error = |n: u32, d: u32| ((f64::from(n) / f64::from(d)).log2() - x).abs();
+
+    let mut best = ((1, 1), x.abs());
+    for d in 1..max_den + 1 {
+        let rounded = (x.exp2() * f64::from(d)).round();
+        // The neighbours matter when the rounded numerator isn't accepted
+        for &n in &[rounded - 1.0, rounded, rounded + 1.0] {
+            if n < 1.0 || n > f64::from(u32::MAX) { continue }
+
+            let n = n as u32;
+            if gcd(u64::from(n), u64::from(d)) != 1 || !accept(n, d) { continue }
+
+            let e = error(n, d);
+            if e < best.1 { best = ((n, d), e) }
+        }
+    }
+
+    best.0
+}
+
+fn largest_prime_factor(n: u32) -> u32 {
+    prime_factors(n).last().cloned().unwrap_or(1)
+}
+
+/// Returns the distinct prime factors of `n` in ascending order
+fn prime_factors(mut n: u32) -> Vec<u32> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p <= n / p {
+        if n.is_multiple_of(p) { factors.push(p) }
+        while n.is_multiple_of(p) { n /= p }
+        p += 1;
+    }
+
+    if n > 1 { factors.push(n) }
+    factors
+}
+
+/// An error that occured while making a `MidiTuning`
+#[derive(Debug, Clone, PartialEq)]
+pub enum MidiTuningError {
+    /// The tuning doesn't give a pitch for some of the notes
+    Unmapped,
+    /// The note is not a MIDI note, `0..=127`
+    NoteOutOfRange(u8),
+    /// The note is given more than once
+    DuplicateNote(u8),
+}
+
+impl ::std::fmt::Display for MidiTuningError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            MidiTuningError::Unmapped => write!(f, "the tuning doesn't map every note"),
+            MidiTuningError::NoteOutOfRange(note) => write!(f, "note {} is out of range", note),
+            MidiTuningError::DuplicateNote(note) => write!(f, "note {} is given twice", note),
+        }
+    }
+}
+
+impl ::std::error::Error for MidiTuningError {}
+
+/// An error that occured while making a `MidiTuning` from a slice of pitches
+#[derive(Debug, Clone, PartialEq)]
+pub enum FromPitchesError {
+    /// There are less than 128 pitches, the length is given
+    TooShort(usize),
+    /// The pitch of the note is zero, negative or NaN
+    NonPositive(u8),
+    /// The pitch of the note is lower than the pitch of the previous one
+    NotAscending(u8),
+}
+
+impl ::std::fmt::Display for FromPitchesError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            FromPitchesError::TooShort(len) => write!(f, "expected 128 pitches, found {}", len),
+            FromPitchesError::NonPositive(note) => write!(f, "the pitch of note {} is not positive", note),
+            FromPitchesError::NotAscending(note) => write!(f, "note {} is lower than the previous one", note),
+        }
+    }
+}
+
+impl ::std::error::Error for FromPitchesError {}
+
+/// An error that occured while loading a `.scl` and `.kbm` pair
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    /// The `.scl` file is invalid
+    Scl(ParseError),
+    /// The `.kbm` file is invalid
+    Kbm(ParseError),
+    /// The reference note of the mapping has no pitch
+    Unmapped,
+}
+
+impl ::std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            LoadError::Scl(ref e) => write!(f, "invalid scale: {}", e),
+            LoadError::Kbm(ref e) => write!(f, "invalid keyboard mapping: {}", e),
+            LoadError::Unmapped => write!(f, "the reference note is unmapped"),
+        }
+    }
+}
+
+impl ::std::error::Error for LoadError {}
+
+/// Loads the text of a `.scl` file along with an optional `.kbm` file
+///
+/// Without a `.kbm`, the scale is mapped linearly with the degree 0 on the middle C
+/// tuned to 261.6256 Hz (see `kbm::Keymap::default()`).
+pub fn load_scl_kbm(scl: &str, kbm: Option<&str>) -> Result<MidiTuning, LoadError> {
+    let scale = scl::parse_scl(scl, Hz(1.0)).map_err(LoadError::Scl)?;
+    let keymap = match kbm {
+        Some(kbm) => kbm::parse_kbm(kbm).map_err(LoadError::Kbm)?,
+        None => kbm::Keymap::default(),
+    };
+
+    keymap.apply(&scale).ok_or(LoadError::Unmapped)
+}
+
+/// An error that occured while parsing a scale
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The text isn't cents, a ratio or an EDO step
+    InvalidInterval(String),
+    /// The scale has no intervals at all
+    Empty,
+    /// The note count of a `.scl` file is not a number
+    InvalidCount(String),
+    /// A `.scl` file has a different number of notes than it declares
+    WrongCount {
+        expected: usize,
+        found: usize,
+    },
+    /// A field of a `.kbm` file is not a valid value
+    InvalidValue(String),
+}
+
+impl ::std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            ParseError::InvalidInterval(ref s) => write!(f, "invalid interval `{}`", s),
+            ParseError::Empty => write!(f, "the scale is empty"),
+            ParseError::InvalidCount(ref s) => write!(f, "invalid note count `{}`", s),
+            ParseError::WrongCount { expected, found } =>
+                write!(f, "expected {} notes, found {}", expected, found),
+            ParseError::InvalidValue(ref s) => write!(f, "invalid value `{}`", s),
+        }
+    }
+}
+
+impl ::std::error::Error for ParseError {}
+
+/// Parses an interval written in the Scala/Scale Workshop notation
+///
+/// A number with a dot is cents (`701.955`), `3/2` or `3` is a ratio
+/// and `7\12` is 7 steps of 12 EDO. Anything after the first whitespace is ignored.
+pub fn parse_interval(s: &str) -> Result<Cents, ParseError> {
+    let invalid = || ParseError::InvalidInterval(s.trim().to_owned());
+    let token = s.split_whitespace().next().ok_or_else(invalid)?;
+
+    if let Some(pos) = token.find('\\') {
+        let steps: i32 = token[..pos].parse().map_err(|_| invalid())?;
+        let division: u32 = token[pos + 1..].parse().map_err(|_| invalid())?;
+        if division == 0 { return Err(invalid()) }
+
+        Ok(Cents(1200.0 * steps as f32 / division as f32))
+    }
+    else if let Some(pos) = token.find('/') {
+        let num: u64 = token[..pos].parse().map_err(|_| invalid())?;
+        let den: u64 = token[pos + 1..].parse().map_err(|_| invalid())?;
+        if num == 0 || den == 0 { return Err(invalid()) }
+
+        Ok(Cents((1200.0 * (num as f64 / den as f64).log2()) as f32))
+    }
+    else if token.contains('.') {
+        token.parse().map(Cents).map_err(|_| invalid())
+    }
+    else {
+        let num: u64 = token.parse().map_err(|_| invalid())?;
+        if num == 0 { return Err(invalid()) }
+
+        Ok(Cents((1200.0 * (num as f64).log2()) as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test] fn cyclic_tuning() {
+        let tuning = CyclicTuning::from_ratios(&[
+            3.0 / 2.0,
+            2.0_f32
+        ], Hz(440.0));
+
+        assert_eq!(tuning.pitch(0).unwrap().0, 440.0);
+        assert_eq!(tuning.pitch(1).unwrap().0.round(), 660.0_f32);
         assert_eq!(tuning.pitch(3).unwrap().0.round(), 1320.0_f32);
         assert_eq!(tuning.pitch(-1).unwrap().0.round(), 330.0_f32);
         assert_eq!(tuning.pitch(-3).unwrap().0.round(), 165.0_f32);
     }
+
+    #[test] fn default_interval() {
+        // `EqualSteps` uses the default `interval`, which must go from `from` up to `to`
+        let steps = EqualSteps::new_a440(Cents(100.0));
+        assert!(steps.interval(0, 1).unwrap().0 > 0.0);
+        assert!((steps.interval(0, 1).unwrap() - Cents(100.0)).0.abs() < 0.01);
+        assert!((steps.interval(3, -2).unwrap() + Cents(500.0)).0.abs() < 0.01);
+    }
+
+    #[test] fn detect_edo() {
+        let edo12: Vec<_> = (1..13).map(|i| Cents(100.0 * i as f32)).collect();
+        assert_eq!(super::detect_edo(&edo12, Cents(0.01)), Some(12));
+
+        let edo24: Vec<_> = (1..25).map(|i| Cents(50.0 * i as f32)).collect();
+        assert_eq!(super::detect_edo(&edo24, Cents(0.01)), Some(24));
+
+        let just = [
+            Cents::from_ratio(9.0 / 8.0),
+            Cents::from_ratio(5.0 / 4.0),
+            Cents::from_ratio(7.0 / 4.0),
+            Cents(1200.0),
+        ];
+        assert_eq!(super::detect_edo(&just, Cents(0.01)), None);
+    }
+
+    #[test] fn cyclic_builder() {
+        let built = CyclicTuningBuilder::new()
+            .reference(Hz(261.63))
+            .add_cents(Cents(400.0))
+            .add_ratio(2.0_f32.powf(700.0 / 1200.0))
+            .period_cents(Cents(1200.0))
+            .build()
+            .unwrap();
+        let direct = CyclicTuning::from_cents(&[
+            Cents(400.0),
+            Cents(700.0),
+            Cents(1200.0)
+        ], Hz(261.63));
+
+        for i in -4..5 {
+            assert_eq!(
+                built.pitch(i).unwrap().0.round(),
+                direct.pitch(i).unwrap().0.round()
+            );
+        }
+
+        let no_period = CyclicTuningBuilder::new().add_cents(Cents(100.0)).build();
+        assert_eq!(no_period.unwrap_err(), BuildError::NoPeriod);
+
+        let descending = CyclicTuningBuilder::new()
+            .add_cents(Cents(700.0))
+            .add_cents(Cents(400.0))
+            .period_cents(Cents(1200.0))
+            .build();
+        assert_eq!(descending.unwrap_err(), BuildError::NotAscending);
+    }
+
+    #[test] fn accessors() {
+        let steps = EqualSteps::new(Cents(78.0), Hz(261.63));
+        assert_eq!(steps.step(), Cents(78.0));
+        assert_eq!(steps.reference(), Hz(261.63));
+
+        assert_eq!(Edo::new(19, Hz(432.0)).reference(), Hz(432.0));
+
+        let cyclic = CyclicTuning::from_cents(&[Cents(1200.0)], Hz(110.0));
+        assert_eq!(cyclic.reference(), Hz(110.0));
+    }
+
+    #[test] fn midi_range() {
+        let tuning = MidiTuning::default();
+        let piano = tuning.range(21, 108);
+
+        assert_eq!(piano.len(), 88);
+        assert_eq!(piano[48], Hz(440.0));
+        assert_eq!(tuning.range(0, 127).len(), 128);
+    }
+
+    #[test] fn midi_from_fn() {
+        let tuning = MidiTuning::from_fn(|n| Hz(f32::from(n) + 1.0));
+
+        assert_eq!(tuning[0], Hz(1.0));
+        assert_eq!(tuning[69], Hz(70.0));
+        assert_eq!(tuning[127], Hz(128.0));
+    }
+
+    #[test] fn carlos_scales() {
+        assert_eq!(EqualSteps::carlos_alpha(A440).step(), Cents(78.0));
+        assert_eq!(EqualSteps::carlos_beta(A440).step(), Cents(63.8));
+        assert_eq!(EqualSteps::carlos_gamma(A440).step(), Cents(35.1));
+
+        // Four Alpha steps make a minor third, nine make a fifth
+        let alpha = EqualSteps::carlos_alpha(A440);
+        let third = alpha.interval(0, 4).unwrap() - Cents::from_ratio(6.0 / 5.0);
+        let fifth = alpha.interval(0, 9).unwrap() - Cents::from_ratio(3.0 / 2.0);
+        assert!(third.0.abs() < 4.0);
+        assert!(fifth.0.abs() < 1.0);
+    }
+
+    #[test] fn equave() {
+        let cyclic = CyclicTuning::from_cents(&[Cents(700.0), Cents(1900.0)], A440);
+        assert_eq!(cyclic.equave(), Some(Cents(1900.0)));
+
+        assert_eq!(Edo::new_a440(19).equave(), Some(Cents(1200.0)));
+        assert_eq!(EqualSteps::carlos_alpha(A440).equave(), None);
+    }
+
+    #[test] fn midi_diff() {
+        let default = MidiTuning::default();
+        assert!(default.diff(&default).iter().all(|c| *c == Cents(0.0)));
+
+        let stretched = MidiTuning::from_tuning(EqualSteps::new_a440(Cents(101.0)), 69).unwrap();
+        let deltas = stretched.diff(&default);
+        assert_eq!(deltas[69].0.round(), 0.0);
+        assert_eq!(deltas[81].0.round(), 12.0);
+        assert_eq!(deltas[57].0.round(), -12.0);
+    }
+
+    #[test] fn midi_fold_octave() {
+        let folded = MidiTuning::default().fold_octave(60);
+
+        assert_eq!(folded.len(), 12);
+        for (i, c) in folded.iter().enumerate() {
+            assert_eq!(c.0.round(), 100.0 * i as f32);
+        }
+
+        assert_eq!(MidiTuning::default().fold_octave(200), vec![Cents(0.0)]);
+        assert_eq!(MidiTuning::default().fold_octave(255), MidiTuning::default().fold_octave(127));
+    }
+
+    #[test] fn edo_named_scale() {
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        let expected = [0.0, 200.0, 400.0, 500.0, 700.0, 900.0, 1100.0, 1200.0];
+        for (i, c) in expected.iter().enumerate() {
+            assert_eq!(major.interval(0, i as i32).unwrap().0.round(), *c);
+        }
+
+        let edo19 = Edo::new_a440(19);
+        let diatonic = edo19.named_scale(&[3, 3, 2, 3, 3, 3, 2]);
+        assert_eq!(diatonic.equave().unwrap().0.round(), 1200.0);
+        assert_eq!(diatonic.pitch(4), edo19.pitch(11));
+    }
+
+    #[test] fn modes_by_brightness() {
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        let modes = major.modes_by_brightness();
+        assert_eq!(modes.len(), 7);
+
+        // Lydian has the raised fourth
+        let lydian = &modes[0];
+        assert_eq!(lydian.steps[2].0.round(), 600.0);
+        assert_eq!(lydian.steps[3].0.round(), 700.0);
+
+        // Locrian has the minor second and the diminished fifth
+        let locrian = &modes[6];
+        assert_eq!(locrian.steps[0].0.round(), 100.0);
+        assert_eq!(locrian.steps[3].0.round(), 600.0);
+    }
+
+    #[test] fn cents_table() {
+        let table = Edo::new_a440(12).cents_table(12);
+        let expected: Vec<_> = (0..12).map(|i| Cents(100.0 * i as f32)).collect();
+        assert_eq!(table, expected);
+
+        let cyclic = CyclicTuning::from_cents(&[Cents(400.0), Cents(700.0), Cents(1200.0)], A440);
+        let table: Vec<_> = cyclic.cents_table(5).iter().map(|c| c.0.round()).collect();
+        assert_eq!(table, vec![0.0, 400.0, 700.0, 1200.0, 1600.0]);
+
+        assert_eq!(MidiTuning::default().cents_table(200).len(), 128);
+    }
+
+    #[test] fn cyclic_from_frequencies() {
+        let tuning = CyclicTuning::from_frequencies(&[Hz(660.0), Hz(550.0)], Hz(880.0), A440);
+
+        assert_eq!(tuning.pitch(1).unwrap().0.round(), 550.0);
+        assert_eq!(tuning.pitch(2).unwrap().0.round(), 660.0);
+        assert_eq!(tuning.pitch(4).unwrap().0.round(), 1100.0);
+    }
+
+    #[test] fn rationalize() {
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        assert_eq!(
+            major.rationalize(5, 8),
+            vec![(9, 8), (5, 4), (4, 3), (3, 2), (5, 3), (15, 8), (2, 1)]
+        );
+
+        let sept = CyclicTuning::from_cents(&[Cents(969.0), Cents(1200.0)], A440);
+        assert_eq!(sept.rationalize(7, 8), vec![(7, 4), (2, 1)]);
+        assert_eq!(sept.rationalize(5, 8)[0], (9, 5));
+
+        // 17/16 is out of reach and 8/8 isn't reduced, but 9/8 is still closer than 1/1
+        let semitone = CyclicTuning::from_cents(&[Cents(103.0), Cents(1200.0)], A440);
+        assert_eq!(semitone.rationalize(5, 8), vec![(9, 8), (2, 1)]);
+    }
+
+    #[test] fn interval_histogram() {
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        let histogram = major.interval_histogram(100.0);
+
+        assert_eq!(histogram, vec![
+            (100.0, 2), (200.0, 5), (300.0, 4), (400.0, 3), (500.0, 6), (600.0, 2),
+            (700.0, 6), (800.0, 3), (900.0, 4), (1000.0, 5), (1100.0, 2),
+        ]);
+    }
+
+    #[test] fn snap_to_edo() {
+        let fifth = (Cents(2400.0) + Cents::from_ratio(5.0 / 4.0)) * 0.25;
+        let mut degrees: Vec<Cents> = (-5..7)
+            .map(|k| (fifth * k as f32).pitch_class())
+            .filter(|c| c.0 > 0.0)
+            .collect();
+        degrees.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        degrees.push(Cents(1200.0));
+        let meantone = CyclicTuning::from_cents(&degrees, A440);
+
+        let edo31 = Edo::new_a440(31);
+        let snapped = meantone.snap_to_edo(&edo31);
+        for (a, b) in meantone.steps.iter().zip(&snapped.steps) {
+            assert!((a.0 - b.0).abs() < 1.5);
+        }
+        assert!(meantone.snap_error(&edo31).0 < 12.0);
+        assert!(meantone.snap_error(&Edo::new_a440(12)).0 > 50.0);
+    }
+
+    #[test] fn maximally_even() {
+        let is_rotation = |tuning: &CyclicTuning, pattern: &[i32]| {
+            let steps: Vec<i32> = (0..pattern.len() as i32)
+                .map(|i| tuning.interval(i, i + 1).unwrap().0.round() as i32 / 100)
+                .collect();
+            (0..pattern.len()).any(|k| {
+                pattern.iter().cycle().skip(k).take(pattern.len()).eq(steps.iter())
+            })
+        };
+
+        let edo12 = Edo::new_a440(12);
+        let diatonic = edo12.maximally_even(7);
+        assert_eq!(diatonic.steps.len(), 7);
+        assert!(is_rotation(&diatonic, &[2, 2, 1, 2, 2, 2, 1]));
+
+        let pentatonic = edo12.maximally_even(5);
+        assert!(is_rotation(&pentatonic, &[2, 2, 3, 2, 3]));
+    }
+
+    #[test] fn midi_pitch_of() {
+        let tuning = MidiTuning::default();
+        for note in 0..128 {
+            assert_eq!(tuning.pitch_of(note), tuning[usize::from(note)]);
+        }
+        for note in 128..256 {
+            assert_eq!(tuning.pitch_of(note as u8), tuning[127]);
+        }
+    }
+
+    #[test] fn cumulative_and_step_sizes() {
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+
+        let cumulative: Vec<_> = major.cumulative_cents().iter().map(|c| c.0.round()).collect();
+        assert_eq!(cumulative, vec![200.0, 400.0, 500.0, 700.0, 900.0, 1100.0, 1200.0]);
+
+        let sizes: Vec<_> = major.step_sizes().iter().map(|c| c.0.round()).collect();
+        assert_eq!(sizes, vec![200.0, 200.0, 100.0, 200.0, 200.0, 200.0, 100.0]);
+    }
+
+    #[test] fn midi_detect_period() {
+        let (period, size) = MidiTuning::default().detect_period(Cents(0.01)).unwrap();
+        assert_eq!(period, 12);
+        assert_eq!(size.0.round(), 1200.0);
+
+        let random = MidiTuning::from_fn(|n| {
+            let hash = (u32::from(n) + 1).wrapping_mul(2654435761);
+            Hz(20.0 + (hash >> 16) as f32 / 8.0)
+        });
+        assert_eq!(random.detect_period(Cents(1.0)), None);
+    }
+
+    #[test] fn midi_detect_non_octave_period() {
+        let tritave = CyclicTuning::from_cents(&[Cents(500.0), Cents(1902.0)], A440);
+        let tuning = MidiTuning::from_tuning(tritave, 69).unwrap();
+
+        let (period, size) = tuning.detect_period(Cents(0.01)).unwrap();
+        assert_eq!(period, 2);
+        assert_eq!(size.0.round(), 1902.0);
+    }
+
+    #[test] fn cyclic_non_finite() {
+        let broken = CyclicTuning::from_cents(&[Cents(700.0), Cents(f32::NAN)], A440);
+        assert_eq!(broken.pitch(1), None);
+        assert_eq!(broken.pitch(2), None);
+
+        let broken = CyclicTuning::from_cents(&[Cents(f32::INFINITY), Cents(1200.0)], A440);
+        assert_eq!(broken.pitch(1), None);
+        assert_eq!(broken.pitch(2).unwrap().0.round(), 880.0);
+    }
+
+    #[test] fn cyclic_into_iter() {
+        let major = Edo::new(12, Hz(261.63)).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        let mut count = 0;
+        for (step, hz) in &major {
+            assert_eq!(Some(hz), major.pitch(step));
+            count += 1;
+        }
+        assert_eq!(count, 7);
+
+        let pitches: Vec<_> = major.into_iter().collect();
+        assert_eq!(pitches[4].0, 4);
+        assert_eq!(pitches[4].1.0.round(), 392.0);
+    }
+
+    #[test] fn partch43() {
+        let partch = CyclicTuning::partch43(Hz(392.0));
+        assert_eq!(partch.cumulative_cents().len(), 43);
+        assert_eq!(partch.equave(), Some(Cents(1200.0)));
+
+        let ratios = partch.ratios().unwrap();
+        assert_eq!(ratios[33], Ratio::new(7, 4));
+        assert_eq!(partch.interval(0, 34).unwrap().0.round(), 969.0);
+        assert_eq!(partch.pitch(25).unwrap().0.round(), 588.0);
+    }
+
+    #[test] fn decompose_step() {
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        assert_eq!(major.decompose_step(9), Some((1, 2)));
+        assert_eq!(major.decompose_step(0), Some((0, 0)));
+        assert_eq!(major.decompose_step(-1), Some((-1, 6)));
+        assert_eq!(major.decompose_step(-7), Some((-1, 0)));
+
+        assert_eq!(Edo::new_a440(12).decompose_step(-13), Some((-2, 11)));
+        assert_eq!(EqualSteps::carlos_alpha(A440).decompose_step(9), None);
+        assert_eq!(MidiTuning::default().decompose_step(9), None);
+    }
+
+    #[test] fn midi_overrides() {
+        let overrides = [(60, Hz(260.0)), (61, Hz(275.0))];
+        let tuning = MidiTuning::from_tuning_with_overrides(Edo::new_a440(12), 69, &overrides).unwrap();
+        let base = MidiTuning::default();
+
+        assert_eq!(tuning[60], Hz(260.0));
+        assert_eq!(tuning[61], Hz(275.0));
+        for n in (0..128).filter(|&n| n != 60 && n != 61) {
+            assert_eq!((tuning[n] / base[n]).0.round(), 0.0);
+        }
+
+        let twice = [(60, Hz(260.0)), (60, Hz(261.0))];
+        assert_eq!(
+            MidiTuning::from_tuning_with_overrides(Edo::new_a440(12), 69, &twice).unwrap_err(),
+            MidiTuningError::DuplicateNote(60)
+        );
+        assert_eq!(
+            MidiTuning::from_tuning_with_overrides(Edo::new_a440(12), 69, &[(200, Hz(1.0))]).unwrap_err(),
+            MidiTuningError::NoteOutOfRange(200)
+        );
+    }
+
+    #[test] fn load_scl_kbm() {
+        let scl = "! major.scl\n!\nJust major\n 7\n!\n 9/8\n 5/4\n 4/3\n 3/2\n 5/3\n 15/8\n 2/1\n";
+
+        let linear = super::load_scl_kbm(scl, None).unwrap();
+        assert_eq!(linear[60].0.round(), 262.0);
+        assert_eq!((linear[61] / linear[60]).0.round(), 204.0);
+        assert_eq!((linear[67] / linear[60]).0.round(), 1200.0);
+
+        let kbm = "12\n0\n127\n60\n69\n440.0\n7\n0\nx\n1\nx\n2\n3\nx\n4\nx\n5\nx\n6\n";
+        let mapped = super::load_scl_kbm(scl, Some(kbm)).unwrap();
+        assert_eq!(mapped[69], Hz(440.0));
+        assert_eq!(mapped[60].0.round(), 264.0);
+        assert_eq!(mapped[62].0.round(), 297.0);
+        assert_eq!(mapped[72].0.round(), 528.0);
+        assert_eq!(mapped[61], MidiTuning::default()[61]);
+
+        assert!(super::load_scl_kbm("bad\n", None).is_err());
+    }
+
+    #[test] fn sample() {
+        let edo = Edo::new_a440(12);
+        let (steps, pitches, cents) = edo.sample(-3..=12).unwrap();
+
+        assert_eq!(steps.len(), 16);
+        assert_eq!(pitches.len(), 16);
+        assert_eq!(cents.len(), 16);
+        for i in 0..16 {
+            assert_eq!(pitches[i], edo.pitch(steps[i]).unwrap());
+            assert_eq!(cents[i], edo.interval(0, steps[i]).unwrap());
+        }
+
+        assert!(MidiTuning::default().sample(-1..=10).is_none());
+    }
+
+    #[test] fn pitch_cents() {
+        let edo = Edo::new_a440(12);
+        assert_eq!(edo.pitch_cents(12).unwrap().0.round(), 1200.0);
+        assert_eq!(edo.pitch_cents(-12).unwrap().0.round(), -1200.0);
+        assert_eq!(edo.pitch_cents(0), Some(Cents(0.0)));
+    }
+
+    #[test] fn rank_two() {
+        let fifth = (Cents(2400.0) + Cents::from_ratio(5.0 / 4.0)) * 0.25;
+        let meantone = RankTwo::new(Cents(1200.0), fifth, 7, Hz(261.63));
+
+        // Stacking fifths upwards from the reference gives the Lydian mode
+        assert!((meantone.interval(0, 4).unwrap() - fifth).0.abs() < 0.01);
+        assert!((meantone.interval(1, 5).unwrap() - fifth).0.abs() < 0.01);
+        assert!((meantone.interval(0, 2).unwrap() - Cents::from_ratio(5.0 / 4.0)).0.abs() < 1.0);
+        assert!((meantone.interval(4, 6).unwrap() - Cents::from_ratio(5.0 / 4.0)).0.abs() < 1.0);
+        assert_eq!(meantone.interval(0, 7).unwrap().0.round(), 1200.0);
+        assert_eq!(meantone.steps_per_equave(), Some(7));
+    }
+
+    #[test] fn steps_in_range() {
+        let edo = Edo::new_a440(12);
+        assert_eq!(edo.steps_in_range(Hz(400.0), Hz(500.0), -24..=24), vec![-1, 0, 1, 2]);
+        assert_eq!(edo.steps_in_range(Hz(400.0), Hz(500.0), 0..=1), vec![0, 1]);
+        assert!(edo.steps_in_range(Hz(500.0), Hz(400.0), -24..=24).is_empty());
+    }
+
+    #[test] fn descending() {
+        let edo = Edo::new_a440(12);
+        let down = edo.clone().descending();
+
+        assert_eq!(down.pitch(7), edo.pitch(-7));
+        assert_eq!(down.pitch(-3), edo.pitch(3));
+        assert_eq!(down.pitch(0), Some(down.reference_pitch()));
+        assert_eq!(down.pitch(i32::MIN), None);
+        assert!(down.interval(0, 12).unwrap().0 < 0.0);
+    }
+
+    #[test] fn cyclic_repeat() {
+        let octaves = CyclicTuning::repeat(Cents(1200.0), Hz(110.0));
+        assert_eq!(octaves.pitch(3).unwrap().0.round(), 880.0);
+        assert_eq!(octaves.pitch(-1).unwrap().0.round(), 55.0);
+        assert_eq!(octaves.steps_per_equave(), Some(1));
+
+        let fifths = CyclicTuning::repeat(Cents::from_ratio(1.5), Hz(100.0));
+        assert_eq!(fifths.pitch(2).unwrap().0.round(), 225.0);
+    }
+
+    #[test] fn edo_from_str_spec() {
+        let edo = Edo::from_str_spec("12edo", A440).unwrap();
+        assert_eq!(edo.steps_per_equave(), Some(12));
+        assert_eq!(edo.equave(), Some(Cents(1200.0)));
+        assert_eq!(edo.pitch(12).unwrap().0.round(), 880.0);
+
+        let edo = Edo::from_str_spec("31ed2", A440).unwrap();
+        assert_eq!(edo.interval(0, 31).unwrap().0.round(), 1200.0);
+
+        let bp = Edo::from_str_spec("13ed3", Hz(100.0)).unwrap();
+        assert_eq!(bp.steps_per_equave(), Some(13));
+        assert!((bp.equave().unwrap() - Cents::from_ratio(3.0)).0.abs() < 0.01);
+        assert_eq!(bp.pitch(13).unwrap().0.round(), 300.0);
+        assert_eq!(bp.pitch(-13).unwrap().0.round(), 33.0);
+
+        assert_eq!(Edo::from_str_spec("abc", A440).unwrap_err(), EdoParseError::Malformed("abc".into()));
+        assert_eq!(Edo::from_str_spec("xedo", A440).unwrap_err(), EdoParseError::InvalidCardinality("x".into()));
+        assert_eq!(Edo::from_str_spec("0edo", A440).unwrap_err(), EdoParseError::InvalidCardinality("0".into()));
+        assert_eq!(Edo::from_str_spec("7edfoo", A440).unwrap_err(), EdoParseError::InvalidEquave("foo".into()));
+        assert_eq!(Edo::from_str_spec("7ed1/2", A440).unwrap_err(), EdoParseError::InvalidEquave("1/2".into()));
+
+        // A decimal equave is a ratio, not cents
+        assert_eq!(Edo::from_str_spec("12ed2.0", A440).unwrap().equave(), Some(Cents(1200.0)));
+        let fifth = Edo::from_str_spec("7ed1.5", A440).unwrap().equave().unwrap();
+        assert!((fifth - Cents::from_ratio(1.5)).0.abs() < 0.01);
+        let fifth = Edo::from_str_spec("7ed3/2", A440).unwrap().equave().unwrap();
+        assert!((fifth - Cents::from_ratio(1.5)).0.abs() < 0.01);
+        assert_eq!(Edo::from_str_spec("7ed0.5", A440).unwrap_err(), EdoParseError::InvalidEquave("0.5".into()));
+        assert_eq!(Edo::from_str_spec("7ed3\\5", A440).unwrap_err(), EdoParseError::InvalidEquave("3\\5".into()));
+
+        // Letter case doesn't matter
+        assert_eq!(Edo::from_str_spec("12EDO", A440).unwrap().equave(), Some(Cents(1200.0)));
+        assert_eq!(Edo::from_str_spec("12EdO", A440).unwrap().steps_per_equave(), Some(12));
+        assert_eq!(Edo::from_str_spec("13ED3", A440).unwrap().equave(), bp.equave());
+    }
+
+    #[test] fn harmonic_errors() {
+        let errors12 = Edo::new_a440(12).harmonic_errors(16);
+        let errors31 = Edo::new_a440(31).harmonic_errors(16);
+        assert_eq!(errors12.len(), 16);
+        assert_eq!(errors12[0], (1, Cents(0.0)));
+        assert_eq!(errors12[15].0, 16);
+
+        assert!((errors12[4].1.0 - 13.7).abs() < 0.1);
+        assert!((errors12[6].1.0 - 31.2).abs() < 0.1);
+        assert!((errors31[4].1.0 - 0.8).abs() < 0.1);
+        assert!((errors31[6].1.0 + 1.1).abs() < 0.1);
+    }
+
+    #[test] fn cyclic_union() {
+        let pentatonic = Edo::new_a440(12).named_scale(&[2, 2, 3, 2, 3]);
+        let complement = CyclicTuning::from_cents(&[
+            Cents(100.0), Cents(300.0), Cents(500.0), Cents(600.0), Cents(700.001),
+            Cents(800.0), Cents(1000.0), Cents(1100.0), Cents(1200.0)
+        ], Hz(1.0));
+
+        let chromatic = pentatonic.union(&complement, Cents(0.01)).unwrap();
+        assert_eq!(chromatic.reference(), A440);
+        assert_eq!(chromatic.steps.len(), 12);
+        for (i, step) in chromatic.steps.iter().enumerate() {
+            assert!((step.0 - 100.0 * (i + 1) as f32).abs() < 0.01);
+        }
+
+        let tritave = CyclicTuning::repeat(Cents::from_ratio(3.0), A440);
+        assert_eq!(pentatonic.union(&tritave, Cents(0.01)).unwrap_err(), UnionError::PeriodMismatch);
+    }
+
+    #[test] fn stack_generators() {
+        let lydian: Vec<f32> = stack(Cents::from_ratio(1.5), Cents(1200.0), 7).iter()
+            .map(|c| c.0.round())
+            .collect();
+        assert_eq!(lydian, vec![0.0, 204.0, 408.0, 612.0, 702.0, 906.0, 1110.0]);
+
+        assert_eq!(stack(Cents(700.0), Cents(1200.0), 0), vec![]);
+        assert_eq!(stack(Cents(-500.0), Cents(1200.0), 2), vec![Cents(0.0), Cents(700.0)]);
+    }
+
+    #[test] fn midi_shift_keys() {
+        let tuning = MidiTuning::default();
+        let up = tuning.shift_keys(12);
+        assert_eq!(up[72], tuning[60]);
+        assert_eq!(up[127], tuning[115]);
+        assert!((up[0] / (tuning[0] - Cents(1200.0))).0.abs() < 0.01);
+        assert!((up[11] / (tuning[0] - Cents(100.0))).0.abs() < 0.01);
+
+        let down = tuning.shift_keys(-5);
+        assert_eq!(down[60], tuning[65]);
+        assert!((down[127] / (tuning[127] + Cents(500.0))).0.abs() < 0.01);
+
+        assert_eq!(tuning.shift_keys(0).pitches, tuning.pitches);
+        assert!((tuning.shift_keys(500)[0] / tuning[0]).0.abs() > 40000.0);
+    }
+
+    #[test] fn edo_similarity() {
+        let edo12 = Edo::new_a440(12);
+        let edo13 = Edo::new_a440(13);
+        let edo24 = Edo::new_a440(24);
+
+        assert_eq!(edo12.similarity(&edo12, 16), 0.0);
+        assert_eq!(edo12.similarity(&edo24, 16), edo24.similarity(&edo12, 16));
+        assert!(edo24.similarity(&edo12, 16) < edo13.similarity(&edo12, 16));
+        // 24 EDO picks a different quarter tone for harmonics 7, 11, 13 and 14
+        assert!((edo24.similarity(&edo12, 16) - 25.0).abs() < 0.1);
+    }
+
+    #[test] fn midi_from_scale() {
+        let chromatic = Edo::new(12, Hz(1.0)).named_scale(&[1; 12]);
+        let tuning = MidiTuning::from_scale(&chromatic, 69, A440).unwrap();
+        let expected = MidiTuning::from_tuning(Edo::new_a440(12), 69).unwrap();
+        assert!(tuning.diff(&expected).iter().all(|d| d.0.abs() < 0.01));
+        assert_eq!(tuning[69], A440);
+
+        assert_eq!(MidiTuning::from_scale(&chromatic, 128, A440).unwrap_err(), MidiTuningError::NoteOutOfRange(128));
+        let broken = CyclicTuning::from_cents(&[Cents(f32::NAN)], Hz(1.0));
+        assert_eq!(MidiTuning::from_scale(&broken, 60, A440).unwrap_err(), MidiTuningError::Unmapped);
+    }
+
+    #[test] fn dyad_consonance() {
+        let ratios = [9.0 / 8.0, 5.0 / 4.0, 4.0 / 3.0, 3.0 / 2.0, 5.0 / 3.0, 15.0 / 8.0, 2.0];
+        let major = CyclicTuning::from_ratios(&ratios, Hz(264.0));
+        let dyads = major.dyad_consonance(40);
+        assert_eq!(dyads.len(), 8 * 7 / 2);
+
+        assert_eq!(dyads[0], ((0, 7), 1.0));
+        let fifths: Vec<(usize, usize)> = dyads[1..4].iter().map(|&(pair, _)| pair).collect();
+        assert_eq!(fifths, vec![(0, 4), (2, 6), (3, 7)]);
+
+        // With a small limit, the 40/27 wolf fifth between 9/8 and 5/3 counts as a fifth
+        let fifths: Vec<(usize, usize)> = major.dyad_consonance(16)[1..5].iter().map(|&(pair, _)| pair).collect();
+        assert_eq!(fifths, vec![(0, 4), (1, 5), (2, 6), (3, 7)]);
+        assert!((dyads[1].1 - 1.0 / 6.0_f32.log2()).abs() < 1e-6);
+        assert!(dyads.windows(2).all(|w| w[0].1 >= w[1].1));
+
+        // The 10 cents step rounds to 1/1
+        let comma = CyclicTuning::from_cents(&[Cents(10.0), Cents(700.0), Cents(1200.0)], Hz(264.0));
+        let dyads = comma.dyad_consonance(16);
+        assert_eq!(dyads.len(), 5);
+        assert_eq!(dyads[0], ((0, 3), 1.0));
+        assert!(dyads.iter().all(|&(pair, score)| pair != (0, 1) && score.is_finite()));
+
+        let partch = CyclicTuning::partch43(Hz(264.0)).dyad_consonance(16);
+        assert!(partch.iter().all(|&(_, score)| score.is_finite()));
+        assert_eq!(partch[0].1, 1.0);
+    }
+
+    #[test] fn midi_from_pitches_checked() {
+        let mut pitches = MidiTuning::default().range(0, 127).to_owned();
+        pitches.push(Hz(0.0));
+        assert_eq!(MidiTuning::from_pitches_checked(&pitches).unwrap().pitches, &pitches[..128]);
+
+        assert_eq!(MidiTuning::from_pitches_checked(&pitches[..100]).unwrap_err(), FromPitchesError::TooShort(100));
+
+        pitches[3] = Hz(-1.0);
+        assert_eq!(MidiTuning::from_pitches_checked(&pitches).unwrap_err(), FromPitchesError::NonPositive(3));
+
+        pitches[3] = Hz(f32::NAN);
+        assert_eq!(MidiTuning::from_pitches_checked(&pitches).unwrap_err(), FromPitchesError::NonPositive(3));
+
+        pitches[3] = pitches[4];
+        pitches[5] = pitches[2];
+        assert_eq!(MidiTuning::from_pitches_checked(&pitches).unwrap_err(), FromPitchesError::NotAscending(5));
+    }
+
+    #[test] fn partials() {
+        let edo = Edo::new_a440(12);
+        assert_eq!(edo.partials(0, 4), Some(vec![Hz(440.0), Hz(880.0), Hz(1320.0), Hz(1760.0)]));
+        assert_eq!(edo.partials(-12, 2), Some(vec![Hz(220.0), Hz(440.0)]));
+        assert_eq!(edo.partials(0, 0), Some(vec![]));
+        assert_eq!(MidiTuning::default().partials(-1, 4), None);
+    }
+
+    #[test] fn quantize() {
+        let edo = Edo::new_a440(12);
+        let (step, error) = edo.quantize(Hz(450.0), -24..=24).unwrap();
+        assert_eq!(step, 0);
+        assert!((error - Cents::from_ratio(450.0 / 440.0)).0.abs() < 0.01);
+        assert_eq!(edo.quantize(Hz(450.0), 3..=5).unwrap().0, 3);
+        assert_eq!(MidiTuning::default().quantize(Hz(450.0), -5..=-1), None);
+
+        let c_major = [0, 2, 4, 5, 7, 9, 11, 12];
+        let detuned: Vec<Hz> = c_major.iter().enumerate()
+            .map(|(i, &s)| Hz(261.63) + Cents(100.0 * s as f32 + if i % 2 == 0 { 12.0 } else { -20.0 }))
+            .collect();
+        let quantized = Edo::new(12, Hz(261.63)).quantize_many(&detuned, -12..=24);
+
+        let steps: Vec<i32> = quantized.iter().map(|q| q.unwrap().0).collect();
+        assert_eq!(steps, c_major);
+        assert!((quantized[0].unwrap().1.0 - 12.0).abs() < 0.01);
+        assert!((quantized[1].unwrap().1.0 + 20.0).abs() < 0.01);
+    }
+
+    #[test] fn set_reference() {
+        let mut edo = Edo::new_a440(12);
+        let before: Vec<Hz> = (-12..13).map(|s| edo.pitch(s).unwrap()).collect();
+        edo.set_reference(Hz(432.0));
+        assert_eq!(edo.reference(), Hz(432.0));
+        for (s, hz) in (-12..13).zip(before) {
+            assert!((edo.pitch(s).unwrap().0 / hz.0 - 432.0 / 440.0).abs() < 1e-6);
+        }
+
+        let mut alpha = EqualSteps::carlos_alpha(A440);
+        alpha.set_reference(Hz(220.0));
+        assert_eq!(alpha.pitch(0), Some(Hz(220.0)));
+
+        let mut fifths = CyclicTuning::repeat(Cents::from_ratio(1.5), A440);
+        fifths.set_reference(Hz(100.0));
+        assert_eq!(fifths.pitch(1).unwrap().0.round(), 150.0);
+    }
+
+    #[test] fn step_for_ratio() {
+        let edo = Edo::new_a440(12);
+        let (step, error) = edo.step_for_ratio(1.5, -24..=24).unwrap();
+        assert_eq!(step, 7);
+        assert!((error.0 - 1.955).abs() < 0.01);
+
+        let (step, error) = edo.step_for_ratio(5.0 / 8.0, -24..=24).unwrap();
+        assert_eq!(step, -8);
+        assert!((error.0 + 13.69).abs() < 0.01);
+    }
+
+    #[test] fn cyclic_period_only() {
+        let octaves = CyclicTuning::from_cents(&[Cents(1200.0)], Hz(440.0));
+        let pitches: Vec<Hz> = (-2..3).map(|s| octaves.pitch(s).unwrap()).collect();
+        assert_eq!(pitches, vec![Hz(110.0), Hz(220.0), Hz(440.0), Hz(880.0), Hz(1760.0)]);
+
+        let tritaves = CyclicTuning::from_cents(&[Cents::from_ratio(3.0)], Hz(90.0));
+        for step in -2..3 {
+            let expected = Hz(90.0) + Cents::from_ratio(3.0) * step as f32;
+            assert_eq!(tritaves.pitch(step), Some(expected));
+        }
+        assert_eq!(tritaves.decompose_step(-2), Some((-2, 0)));
+    }
+
+    #[test] fn primes() {
+        let ratios = [(9, 8), (5, 4), (4, 3), (3, 2), (5, 3), (15, 8), (2, 1)];
+        let ratios: Vec<Ratio> = ratios.iter().map(|&(n, d)| Ratio::new(n, d)).collect();
+        let major = CyclicTuning::from_exact_ratios(&ratios, Hz(264.0));
+        assert_eq!(major.primes(), Some(vec![2, 3, 5]));
+
+        let harmonic = CyclicTuning::from_exact_ratios(&[Ratio::new(5, 4), Ratio::new(3, 2), Ratio::new(7, 4), Ratio::new(2, 1)], Hz(264.0));
+        assert_eq!(harmonic.primes(), Some(vec![2, 3, 5, 7]));
+
+        assert_eq!(CyclicTuning::partch43(Hz(264.0)).primes(), Some(vec![2, 3, 5, 7, 11]));
+        assert_eq!(Edo::new_a440(12).named_scale(&[12]).primes(), None);
+        assert_eq!(prime_factors(1), vec![]);
+        assert_eq!(prime_factors(360), vec![2, 3, 5]);
+
+        // The largest prime below 2^32
+        assert_eq!(prime_factors(4294967291), vec![4294967291]);
+        assert_eq!(prime_factors(u32::MAX), vec![3, 5, 17, 257, 65537]);
+        let big = CyclicTuning::from_exact_ratios(&[Ratio::new(4294967291, 1)], Hz(1.0));
+        assert_eq!(big.primes(), Some(vec![4294967291]));
+        assert_eq!(largest_prime_factor(1), 1);
+    }
+
+    #[test] fn pitch_fractional() {
+        let edo = Edo::new_a440(12);
+        assert_eq!(edo.pitch_fractional(3.0), edo.pitch(3));
+        assert_eq!(edo.pitch_fractional(-3.0), edo.pitch(-3));
+
+        let mean = (edo.pitch(0).unwrap().0 * edo.pitch(1).unwrap().0).sqrt();
+        assert!((edo.pitch_fractional(0.5).unwrap().0 - mean).abs() < 0.001);
+        assert!((edo.pitch_fractional(0.5).unwrap() / A440 - Cents(50.0)).0.abs() < 0.001);
+        assert!((edo.pitch_fractional(-1.25).unwrap() / A440 + Cents(125.0)).0.abs() < 0.001);
+
+        let major = Edo::new_a440(12).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        assert!((major.pitch_fractional(2.5).unwrap() / A440 - Cents(450.0)).0.abs() < 0.001);
+        assert_eq!(edo.pitch_fractional(f32::NAN), None);
+        assert_eq!(MidiTuning::default().pitch_fractional(127.5), None);
+    }
+
+    #[test] fn steps_approximating() {
+        let edo = Edo::new_a440(12);
+        assert_eq!(edo.steps_approximating(1.5, Cents(3.0), -24..=24), vec![-17, -5, 7, 19]);
+        assert_eq!(edo.steps_approximating(1.5, Cents(1.0), -24..=24), vec![]);
+        // A fifth down is a fourth up, reduced by an octave
+        assert_eq!(edo.steps_approximating(2.0 / 3.0, Cents(3.0), 0..=12), vec![5]);
+        assert_eq!(edo.steps_approximating(7.0 / 4.0, Cents(35.0), 0..=24), vec![10, 22]);
+
+        let bp = Edo::from_str_spec("13ed3", A440).unwrap();
+        assert_eq!(bp.steps_approximating(7.0 / 3.0, Cents(5.0), 0..=26), vec![10, 23]);
+    }
+
+    #[test] fn offset_tuning() {
+        let mut offsets = HashMap::new();
+        offsets.insert(5, Cents(3.0));
+        offsets.insert(0, Cents(10.0));
+        let edo = Edo::new_a440(12);
+        let tuning = OffsetTuning::new(edo.clone(), offsets);
+
+        assert_eq!(tuning.reference_pitch(), A440);
+        assert_eq!(tuning.pitch(0), Some(A440));
+        assert_eq!(tuning.pitch(5), Some(edo.pitch(5).unwrap() + Cents(3.0)));
+        for step in (-24..25).filter(|&s| s != 5) {
+            assert_eq!(tuning.pitch(step), edo.pitch(step));
+        }
+        assert!((tuning.interval(0, 5).unwrap() - Cents(503.0)).0.abs() < 0.01);
+    }
+
+    #[test] fn tuned_note() {
+        assert_eq!(TunedNote { note: 69, offset: Cents(0.0) }.frequency(), A440);
+        assert_eq!(TunedNote::from_hz(A440), TunedNote { note: 69, offset: Cents(0.0) });
+
+        let quarter = TunedNote { note: 60, offset: Cents(50.0) }.frequency();
+        let note = TunedNote::from_hz(quarter);
+        assert!(note.note == 60 || note.note == 61);
+        assert!(note.offset.0.abs() <= 50.01);
+        assert!((note.frequency() / quarter).0.abs() < 0.01);
+
+        let note = TunedNote::from_hz(Hz(450.0));
+        assert_eq!(note.note, 69);
+        assert!((note.offset.0 - 38.9).abs() < 0.1);
+
+        assert_eq!(TunedNote::from_hz(Hz(1.0)).note, 0);
+        assert!(TunedNote::from_hz(Hz(1.0)).offset.0 < -50.0);
+    }
+
+    #[test] fn midi_error_stats() {
+        let edo = Edo::new_a440(19);
+        let table = MidiTuning::from_tuning(edo.clone(), 69).unwrap();
+        assert_eq!(table.error_stats(&edo, 69), (Cents(0.0), Cents(0.0)));
+
+        let (rms, max) = MidiTuning::default().error_stats(&Edo::new_a440(24), 69);
+        assert!(rms.0 > 0.0 && rms.0 < max.0);
+        assert!((max.0 - 3450.0).abs() < 0.5);
+
+        let shifted = MidiTuning::from_fn(|n| table[usize::from(n)] + Cents(if n == 0 { 6.0 } else { 3.0 }));
+        let (rms, max) = shifted.error_stats(&edo, 69);
+        assert!((max.0 - 6.0).abs() < 0.01);
+        assert!(rms.0 > 3.0 && rms.0 < 3.1);
+
+        // Only note 127 is mapped, to the step 0 of the default table
+        let (rms, max) = table.error_stats(&MidiTuning::default(), 127);
+        assert_eq!(rms, max);
+        assert_eq!(max, (table[127] / MidiTuning::default()[0]).abs());
+    }
+
+    #[test] fn midi_phase_increments() {
+        let increments = MidiTuning::default().phase_increments(44100.0);
+        assert_eq!(increments.len(), 128);
+        assert!((increments[69] - 0.009977).abs() < 1e-6);
+        assert_eq!(increments[57] * 2.0, increments[69]);
+    }
+
+    #[test] fn equal_steps_from_edo_float() {
+        let steps = EqualSteps::from_edo_float(12.0, A440);
+        let edo = Edo::new_a440(12);
+        assert_eq!(steps.step(), edo.step_size());
+        for step in -24..25 {
+            assert_eq!(steps.pitch(step), edo.pitch(step));
+        }
+
+        let steps = EqualSteps::from_edo_float(12.5, A440);
+        assert_eq!(steps.step(), Cents(96.0));
+        assert_eq!(steps.pitch(25).unwrap().0.round(), 1760.0);
+    }
+
+    #[test] fn walk_steps() {
+        let edo = Edo::new(12, Hz(261.63));
+        let pitches: Vec<f32> = edo.walk_steps(0, &[2, 2, 1]).iter().map(|hz| hz.unwrap().0.round()).collect();
+        assert_eq!(pitches, vec![262.0, 294.0, 330.0, 349.0]);
+
+        assert_eq!(edo.walk_steps(3, &[]), vec![edo.pitch(3)]);
+        assert_eq!(MidiTuning::default().walk_steps(1, &[-1, -1, 2]), vec![
+            Some(MidiTuning::default()[1]), Some(MidiTuning::default()[0]), None, Some(MidiTuning::default()[1])
+        ]);
+    }
+
+    #[test] fn edo_transcribe() {
+        let edo = Edo::new_a440(12);
+        let steps = [0, 2, 4, 5, 7, -5, -12, 12, 3];
+        let melody: Vec<Hz> = steps.iter().map(|&s| edo.pitch(s).unwrap()).collect();
+        assert_eq!(edo.transcribe(&melody), steps);
+
+        let detuned: Vec<Hz> = melody.iter().map(|&hz| hz + Cents(-30.0)).collect();
+        assert_eq!(edo.transcribe(&detuned), steps);
+        assert_eq!(Edo::new_a440(24).transcribe(&melody[..3]), vec![0, 4, 8]);
+    }
+
+    #[test] fn cyclic_with_reference_at() {
+        let major = [200.0, 400.0, 500.0, 700.0, 900.0, 1100.0, 1200.0];
+        let major: Vec<Cents> = major.iter().map(|&c| Cents(c)).collect();
+        let tuning = CyclicTuning::with_reference_at(&major, A440, 5);
+
+        assert!((tuning.pitch(5).unwrap() / A440).0.abs() < 0.001);
+        assert!((tuning.pitch(0).unwrap().0 - 261.63).abs() < 0.01);
+        let plain = CyclicTuning::from_cents(&major, Hz(261.6256));
+        for step in -7..15 {
+            assert!((tuning.pitch(step).unwrap() / plain.pitch(step).unwrap()).0.abs() < 0.01);
+        }
+
+        let tuning = CyclicTuning::with_reference_at(&major, A440, -2);
+        assert!((tuning.pitch(-2).unwrap() / A440).0.abs() < 0.001);
+    }
+
+    #[test] fn midi_step_intervals() {
+        let intervals = MidiTuning::default().step_intervals();
+        assert!(intervals.iter().all(|c| (c.0 - 100.0).abs() < 0.01));
+
+        // Stretched like a piano: every step is a bit larger than the previous one
+        let stretched = MidiTuning::from_fn(|n| A440 + Cents((f32::from(n) - 69.0) * 100.0 + 0.002 * f32::from(n) * f32::from(n)));
+        let intervals = stretched.step_intervals();
+        assert!((intervals[0].0 - 100.0).abs() < 0.01 && (intervals[126].0 - 100.5).abs() < 0.01);
+        assert!(intervals.windows(2).all(|w| w[1].0 > w[0].0 - 0.01));
+    }
+
+    #[test] fn fine_edos() {
+        assert_eq!(Edo::quarter_tone(A440).interval(0, 1), Some(Cents(50.0)));
+        assert_eq!(Edo::eighth_tone(A440).interval(0, 2), Some(Cents(50.0)));
+        assert_eq!(Edo::sixth_tone(A440).steps_per_equave(), Some(36));
+        assert_eq!(Edo::sixth_tone(A440).interval(0, 12), Some(Cents(400.0)));
+    }
+
+    #[test] fn enharmonic() {
+        let edo = Edo::new_a440(12);
+        assert!(edo.are_enharmonic(1, 13));
+        assert!(edo.are_enharmonic(1, -11));
+        assert!(!edo.are_enharmonic(1, 2));
+        assert_eq!(edo.enharmonic_class(-1), 11);
+        assert_eq!(edo.enharmonic_class(25), 1);
+    }
+
+    #[test] fn rereference() {
+        let edo = Edo::new_a440(12);
+        let moved = edo.rereference(Hz(432.0));
+        assert_eq!(moved.reference_pitch(), Hz(432.0));
+        assert_eq!(moved.pitch(0), Some(Hz(432.0)));
+        for step in -12..13 {
+            assert!((moved.interval(0, step).unwrap() - edo.interval(0, step).unwrap()).0.abs() < 0.01);
+            assert!((moved.pitch(step).unwrap().0 / edo.pitch(step).unwrap().0 - 432.0 / 440.0).abs() < 1e-5);
+        }
+
+        let table = MidiTuning::default();
+        assert_eq!(table.rereference(A440).pitch(-1), None);
+    }
+
+    #[test] fn midi_match_frequency() {
+        let table = MidiTuning::default();
+        assert_eq!(table.match_frequency(A440, 2.0), (69, 0));
+        assert_eq!(table.match_frequency(A440 + Cents(20.0), 2.0), (69, 819));
+        assert_eq!(table.match_frequency(A440 - Cents(20.0), 12.0), (69, -137));
+        assert_eq!(table.match_frequency(A440 + Cents(80.0), 2.0), (70, -819));
+
+        // A quarter tone table on a 12 EDO synth: note 71 is a semitone above A, so it's bent down
+        let quarter = MidiTuning::from_tuning(Edo::quarter_tone(A440), 69).unwrap();
+        assert_eq!(quarter.match_frequency(quarter[71], 2.0), (71, -4096));
+        assert_eq!(quarter.match_frequency(quarter[70], 2.0), (70, -2048));
+        assert_eq!(quarter.match_frequency(Hz(20000.0), 2.0), (127, 8191));
+    }
+
+    #[test] fn cyclic_expand() {
+        let major = Edo::new(12, Hz(261.63)).named_scale(&[2, 2, 1, 2, 2, 2, 1]);
+        let pitches = major.expand(2);
+        assert_eq!(pitches.len(), 15);
+        assert_eq!(pitches[0], Hz(261.63));
+        assert!((pitches[14] / Hz(261.63 * 4.0)).0.abs() < 0.01);
+        assert!((pitches[9] / major.pitch(9).unwrap()).0.abs() < 0.001);
+
+        assert_eq!(major.expand(0), vec![Hz(261.63)]);
+    }
 }
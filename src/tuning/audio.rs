@@ -0,0 +1,44 @@
+//! Test tones for auditioning tunings
+
+use std::f32::consts::PI;
+
+use super::Tuning;
+
+/// Renders `steps` of a tuning as a sequence of sine tones
+///
+/// Every note lasts `note_secs` seconds. Unmapped steps are rendered as silence.
+/// The result is a mono buffer of samples in `[-1, 1]`, ready to be played or written to a file.
+pub fn render_sequence(tuning: &dyn Tuning, steps: &[i32], sample_rate: u32, note_secs: f32) -> Vec<f32> {
+    let note_len = (note_secs * sample_rate as f32).round() as usize;
+    let mut samples = Vec::with_capacity(note_len * steps.len());
+
+    for &step in steps {
+        match tuning.pitch(step) {
+            Some(hz) => {
+                let phase_inc = 2.0 * PI * hz.0 / sample_rate as f32;
+                samples.extend((0..note_len).map(|i| (phase_inc * i as f32).sin()));
+            },
+            None => samples.extend((0..note_len).map(|_| 0.0)),
+        }
+    }
+
+    samples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuning::Edo;
+
+    #[test] fn render() {
+        let edo = Edo::new_a440(12);
+        let buffer = render_sequence(&edo, &[0, 7, 12], 44100, 0.5);
+        assert_eq!(buffer.len(), 3 * 22050);
+
+        // A sine wave crosses zero twice per period
+        let fifth = &buffer[22050..44100];
+        let crossings = fifth.windows(2).filter(|w| (w[0] < 0.0) != (w[1] < 0.0)).count();
+        let freq = crossings as f32 / (2.0 * 0.5);
+        assert!((freq - edo.pitch(7).unwrap().0).abs() < 3.0);
+    }
+}
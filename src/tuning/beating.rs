@@ -0,0 +1,45 @@
+//! Beat rates of tempered intervals
+
+use {Hz, Cents};
+
+/// Finds a tempered fifth for which the C-E third and the C-G fifth beat at a given ratio
+///
+/// The fifth is assumed to be narrow (between quarter-comma meantone and pure), with E
+/// reached by four such fifths minus two octaves, like in meantones and well-temperaments.
+/// `reference` is the pitch of C. The fifth beats at `|3C - 2G|` and the third beats at
+/// `|5C - 4E|`, and `target_ratio` is the third beat rate divided by the fifth beat rate.
+/// Both rates are proportional to `reference`, so the result depends only on the ratio.
+pub fn solve_fifth(reference: Hz, target_ratio: f32) -> Cents {
+    let c = f64::from(reference.0);
+    let beats = |fifth: f64| {
+        let g = c * fifth;
+        let e = c * fifth.powi(4) / 4.0;
+        let fifth_beats = (3.0 * c - 2.0 * g).abs();
+        let third_beats = (5.0 * c - 4.0 * e).abs();
+        third_beats - f64::from(target_ratio) * fifth_beats
+    };
+
+    // The third is pure at the lower end and the fifth is pure at the upper one
+    let (mut lo, mut hi) = (5.0_f64.powf(0.25), 1.5_f64);
+    for _ in 0..64 {
+        let mid = 0.5 * (lo + hi);
+        if beats(mid) < 0.0 { lo = mid } else { hi = mid }
+    }
+
+    Cents((1200.0 * (0.5 * (lo + hi)).log2()) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test] fn equal_beating() {
+        let fifth = solve_fifth(Hz(261.63), 1.0);
+        assert!(fifth.0 > 696.0 && fifth.0 < 700.0);
+
+        // The ratio does not depend on the reference
+        assert_eq!(solve_fifth(Hz(440.0), 1.0).0.round(), fifth.0.round());
+        // No beating of the third is quarter-comma meantone
+        assert_eq!(solve_fifth(Hz(440.0), 0.0).0.round(), 697.0);
+    }
+}
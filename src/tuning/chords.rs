@@ -0,0 +1,65 @@
+//! Just intonation chords
+
+use Hz;
+
+/// A kind of chord, defined by its harmonic proportions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChordQuality {
+    /// 4:5:6
+    Major,
+    /// 10:12:15
+    Minor,
+    /// 16:20:25
+    Augmented,
+    /// 25:30:36
+    Diminished,
+    /// 4:5:6:7, with the harmonic seventh
+    Dominant7,
+    /// 8:10:12:15
+    Major7,
+    /// 10:12:15:18
+    Minor7,
+}
+
+impl ChordQuality {
+    /// Returns the proportions of the chord, from the root upwards
+    pub fn proportions(self) -> &'static [u32] {
+        match self {
+            ChordQuality::Major => &[4, 5, 6],
+            ChordQuality::Minor => &[10, 12, 15],
+            ChordQuality::Augmented => &[16, 20, 25],
+            ChordQuality::Diminished => &[25, 30, 36],
+            ChordQuality::Dominant7 => &[4, 5, 6, 7],
+            ChordQuality::Major7 => &[8, 10, 12, 15],
+            ChordQuality::Minor7 => &[10, 12, 15, 18],
+        }
+    }
+}
+
+/// Returns the pitches of a just chord built upwards from `root`
+pub fn just_chord(root: Hz, quality: ChordQuality) -> Vec<Hz> {
+    let proportions = quality.proportions();
+    let base = proportions[0] as f32;
+    proportions.iter().map(|&p| root * (p as f32 / base)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test] fn chords() {
+        assert_eq!(just_chord(Hz(440.0), ChordQuality::Major), vec![Hz(440.0), Hz(550.0), Hz(660.0)]);
+        let minor: Vec<f32> = just_chord(Hz(400.0), ChordQuality::Minor).iter().map(|hz| hz.0.round()).collect();
+        assert_eq!(minor, vec![400.0, 480.0, 600.0]);
+
+        let dominant = just_chord(Hz(440.0), ChordQuality::Dominant7);
+        assert_eq!(dominant.len(), 4);
+        assert_eq!(dominant[3], Hz(440.0 * 7.0 / 4.0));
+
+        for &quality in &[ChordQuality::Augmented, ChordQuality::Diminished, ChordQuality::Major7, ChordQuality::Minor7] {
+            let chord = just_chord(Hz(100.0), quality);
+            assert_eq!(chord[0], Hz(100.0));
+            assert!(chord.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Frequency tables as `note,frequency` CSV
+
+use std::fmt;
+
+use super::MidiTuning;
+use Hz;
+
+/// An error that occured while parsing a CSV frequency table
+#[derive(Debug, Clone, PartialEq)]
+pub enum CsvError {
+    /// The line (counting from 1) isn't a `note,frequency` pair
+    Syntax(usize),
+    /// The line (counting from 1) has a note outside of `0..=127`
+    NoteOutOfRange(usize),
+    /// The note is listed more than once
+    DuplicateNote(u8),
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CsvError::Syntax(line) => write!(f, "line {} is not a note,frequency pair", line),
+            CsvError::NoteOutOfRange(line) => write!(f, "line {} has a note out of range", line),
+            CsvError::DuplicateNote(note) => write!(f, "note {} is listed twice", note),
+        }
+    }
+}
+
+impl ::std::error::Error for CsvError {}
+
+/// Parses a `note,frequency` table, one note per line
+///
+/// Blank lines are skipped. Notes missing from the table keep their 12 EDO pitch
+/// (A440 at note 69), like in `MidiTuning::default()`.
+pub fn parse_csv(text: &str) -> Result<MidiTuning, CsvError> {
+    let mut tuning = MidiTuning::default();
+    let mut seen = [false; 128];
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        if line.trim().is_empty() { continue }
+
+        let mut fields = line.split(',').map(str::trim);
+        let (note, freq) = match (fields.next(), fields.next(), fields.next()) {
+            (Some(note), Some(freq), None) => (note, freq),
+            _ => return Err(CsvError::Syntax(line_no)),
+        };
+
+        let note: i64 = note.parse().map_err(|_| CsvError::Syntax(line_no))?;
+        let freq: f32 = freq.parse().map_err(|_| CsvError::Syntax(line_no))?;
+        if !(0..128).contains(&note) { return Err(CsvError::NoteOutOfRange(line_no)) }
+
+        let note = note as usize;
+        if seen[note] { return Err(CsvError::DuplicateNote(note as u8)) }
+        seen[note] = true;
+        tuning.pitches[note] = Hz(freq);
+    }
+
+    Ok(tuning)
+}
+
+/// Writes the table as `note,frequency` lines
+pub fn to_csv(tuning: &MidiTuning) -> String {
+    tuning.pitches.iter().enumerate()
+        .map(|(note, hz)| format!("{},{}\n", note, hz.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuning::EqualSteps;
+    use Cents;
+
+    #[test] fn round_trip() {
+        let tuning = MidiTuning::from_tuning(EqualSteps::new_a440(Cents(101.0)), 69).unwrap();
+        let parsed = parse_csv(&to_csv(&tuning)).unwrap();
+        assert_eq!(parsed.pitches, tuning.pitches);
+    }
+
+    #[test] fn errors() {
+        let partial = parse_csv("69, 432.0\n\n70,457.7\n").unwrap();
+        assert_eq!(partial[69], Hz(432.0));
+        assert_eq!(partial[60], MidiTuning::default()[60]);
+
+        assert_eq!(parse_csv("1,2,3").unwrap_err(), CsvError::Syntax(1));
+        assert_eq!(parse_csv("60,1.0\n128,1.0").unwrap_err(), CsvError::NoteOutOfRange(2));
+        assert_eq!(parse_csv("60,1.0\n60,2.0").unwrap_err(), CsvError::DuplicateNote(60));
+    }
+}
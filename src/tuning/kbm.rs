@@ -0,0 +1,211 @@
+//! The [Scala](http://www.huygens-fokker.org/scala/help.htm#mappings) `.kbm` keyboard mapping format
+
+use super::{CyclicTuning, MidiTuning, Tuning, ParseError};
+use super::scl::to_scl;
+use {Hz, Cents};
+
+/// A keyboard mapping: which scale degree each MIDI note plays
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keymap {
+    /// Degrees of the notes from `middle_note` up, repeating every `mapping.len()` notes.
+    /// `None` is an unmapped note. Empty mapping means a linear one.
+    mapping: Vec<Option<i32>>,
+    first_note: u8,
+    last_note: u8,
+    /// The note of the degree 0
+    middle_note: u8,
+    reference_note: u8,
+    reference_freq: Hz,
+    /// The degree one mapping period above the degree 0
+    octave_degree: i32,
+}
+
+impl Keymap {
+    /// Creates a linear mapping of all MIDI notes, with the degree 0 at `middle_note`
+    /// and `reference_note` tuned to `reference_freq`
+    pub fn linear(middle_note: u8, reference_note: u8, reference_freq: Hz) -> Self {
+        Keymap {
+            mapping: Vec::new(),
+            first_note: 0,
+            last_note: 127,
+            middle_note, reference_note, reference_freq,
+            octave_degree: 0,
+        }
+    }
+
+    /// Returns the scale degree of a note, or `None` if it's unmapped
+    pub fn degree(&self, note: u8) -> Option<i32> {
+        if note < self.first_note || note > self.last_note { return None }
+
+        let offset = i32::from(note) - i32::from(self.middle_note);
+        if self.mapping.is_empty() { return Some(offset) }
+
+        let size = self.mapping.len() as i32;
+        self.mapping[offset.rem_euclid(size) as usize]
+            .map(|degree| degree + offset.div_euclid(size) * self.octave_degree)
+    }
+
+    /// Maps the scale to MIDI notes
+    ///
+    /// Unmapped notes keep their 12 EDO pitch (A440 at note 69), like in `MidiTuning::default()`.
+    /// Returns `None` if the reference note is unmapped.
+    pub fn apply(&self, scale: &CyclicTuning) -> Option<MidiTuning> {
+        let reference = scale.pitch(self.degree(self.reference_note)?)?;
+
+        let mut tuning = MidiTuning::default();
+        for note in 0..128 {
+            let pitch = self.degree(note).and_then(|degree| scale.pitch(degree));
+            if let Some(hz) = pitch {
+                tuning.pitches[usize::from(note)] = self.reference_freq + hz / reference;
+            }
+        }
+
+        Some(tuning)
+    }
+}
+
+/// The default mapping: linear, with the degree 0 on the middle C tuned to 261.6256 Hz
+impl ::std::default::Default for Keymap {
+    fn default() -> Self {
+        Self::linear(60, 60, Hz(261.625_58))
+    }
+}
+
+/// The largest mapping size accepted by `parse_kbm`, much more than any keyboard needs
+const MAX_MAPPING_SIZE: usize = 1 << 16;
+
+/// Parses a `.kbm` file
+///
+/// Mappings of more than 65536 notes are rejected as `ParseError::InvalidValue`.
+pub fn parse_kbm(text: &str) -> Result<Keymap, ParseError> {
+    let mut lines = text.lines()
+        .filter(|l| !l.starts_with('!'))
+        .map(|l| l.split_whitespace().next().unwrap_or(""));
+
+    let mut field = || lines.next().ok_or(ParseError::Empty);
+    let invalid = |s: &str| ParseError::InvalidValue(s.to_owned());
+
+    let size = field()?;
+    let size: usize = match size.parse() {
+        Ok(n) if n <= MAX_MAPPING_SIZE => n,
+        _ => return Err(invalid(size)),
+    };
+
+    let mut note = || -> Result<u8, ParseError> {
+        let s = field()?;
+        match s.parse() {
+            Ok(n) if n < 128 => Ok(n),
+            _ => Err(invalid(s)),
+        }
+    };
+    let first_note = note()?;
+    let last_note = note()?;
+    let middle_note = note()?;
+    let reference_note = note()?;
+
+    let freq = field()?;
+    let reference_freq = Hz(freq.parse().map_err(|_| invalid(freq))?);
+    let octave = field()?;
+    let octave_degree = octave.parse().map_err(|_| invalid(octave))?;
+
+    // Missing entries at the end are unmapped
+    let mut mapping = Vec::with_capacity(size);
+    for _ in 0..size {
+        mapping.push(match lines.next() {
+            None | Some("x") | Some("X") => None,
+            Some(s) => Some(s.parse().map_err(|_| invalid(s))?),
+        });
+    }
+
+    Ok(Keymap {
+        mapping,
+        first_note, last_note, middle_note,
+        reference_note, reference_freq,
+        octave_degree,
+    })
+}
+
+impl MidiTuning {
+    /// Factors a periodic table back into a `.scl` scale and a linear `.kbm` mapping
+    ///
+    /// The scale is made of the `period_notes` notes above the middle C, which becomes
+    /// the degree 0 and the reference note of the mapping. Returns `None` unless the table
+    /// repeats every `period_notes` notes within 0.01 cents (like in `detect_period`),
+    /// and the period is at most 64 notes.
+    pub fn to_scl_kbm(&self, period_notes: usize) -> Option<(String, String)> {
+        const MIDDLE: usize = 60;
+
+        if period_notes > 64 { return None }
+        self.period_size(period_notes, Cents(0.01))?;
+
+        let base = self.pitches[MIDDLE];
+        let mut steps: Vec<Cents> = self.pitches[MIDDLE + 1..MIDDLE + period_notes].iter()
+            .map(|&hz| hz / base)
+            .collect();
+
+        // Measure the period over the whole table, so that rounding errors don't add up
+        let periods = 127 / period_notes;
+        steps.push((self.pitches[periods * period_notes] / self.pitches[0]) * (1.0 / periods as f32));
+        let scl = to_scl(&CyclicTuning::from_cents(&steps, base), "Exported from a MIDI tuning table");
+
+        let mut kbm = format!(
+            "! Linear mapping of the exported scale\n{}\n0\n127\n{}\n{}\n{:.6}\n{}\n! mapping\n",
+            period_notes, MIDDLE, MIDDLE, base.0, period_notes
+        );
+        for degree in 0..period_notes {
+            kbm.push_str(&format!("{}\n", degree));
+        }
+
+        Some((scl, kbm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuning::{RankTwo, load_scl_kbm};
+
+    #[test] fn white_keys() {
+        let kbm = "! white.kbm\n12\n0\n127\n60\n69\n440.0\n7\n! mapping\n0\nx\n1\nx\n2\n3\nx\n4\nx\n5\nx\n6\n";
+        let keymap = parse_kbm(kbm).unwrap();
+
+        assert_eq!(keymap.degree(60), Some(0));
+        assert_eq!(keymap.degree(61), None);
+        assert_eq!(keymap.degree(74), Some(8));
+        assert_eq!(keymap.degree(59), Some(-1));
+
+        let edo = CyclicTuning::from_cents(&[Cents(100.0), Cents(200.0), Cents(300.0)], Hz(1.0));
+        let tuning = keymap.apply(&edo).unwrap();
+        assert_eq!(tuning[69], Hz(440.0));
+        assert_eq!(tuning[61], MidiTuning::default()[61]);
+    }
+
+    #[test] fn errors() {
+        assert_eq!(parse_kbm("12\n0\n"), Err(ParseError::Empty));
+        assert_eq!(parse_kbm("12\n0\n200\n"), Err(ParseError::InvalidValue("200".to_owned())));
+        assert_eq!(
+            parse_kbm("4000000000\n0\n127\n60\n69\n440\n12\n"),
+            Err(ParseError::InvalidValue("4000000000".to_owned()))
+        );
+        assert!(parse_kbm("65536\n0\n127\n60\n69\n440\n12\n").is_ok());
+    }
+
+    #[test] fn scl_kbm_export() {
+        let fifth = (Cents(2400.0) + Cents::from_ratio(5.0 / 4.0)) * 0.25;
+        let meantone = RankTwo::new(Cents(1200.0), fifth, 7, Hz(1.0));
+        let table = MidiTuning::from_tuning(meantone, 62).unwrap();
+
+        let (scl, kbm) = table.to_scl_kbm(7).unwrap();
+        let loaded = load_scl_kbm(&scl, Some(&kbm)).unwrap();
+        assert!(loaded.diff(&table).iter().all(|d| d.0.abs() < 0.01));
+
+        assert!(table.to_scl_kbm(14).is_some());
+        assert_eq!(table.to_scl_kbm(5), None);
+        assert_eq!(table.to_scl_kbm(0), None);
+        assert_eq!(MidiTuning::default().to_scl_kbm(65), None);
+
+        let (scl, kbm) = MidiTuning::default().to_scl_kbm(1).unwrap();
+        let loaded = load_scl_kbm(&scl, Some(&kbm)).unwrap();
+        assert!(loaded.diff(&MidiTuning::default()).iter().all(|d| d.0.abs() < 0.01));
+    }
+}
@@ -0,0 +1,59 @@
+//! Melodies in just intonation and their pitch drift
+
+use {Hz, Cents};
+
+/// A just intonation lattice, walked by melodic ratios from a starting pitch
+#[derive(Debug, Clone)]
+pub struct Lattice {
+    start: Hz,
+}
+
+impl Lattice {
+    /// Creates a lattice with the walk starting at `start`
+    pub fn new(start: Hz) -> Self {
+        Lattice {
+            start
+        }
+    }
+
+    /// Returns the starting pitch
+    pub fn start(&self) -> Hz {
+        self.start
+    }
+
+    /// Follows the melodic intervals `(num, den)` one after another from the starting pitch
+    ///
+    /// Returns the final pitch and its drift: the interval from the nearest octave
+    /// of the starting pitch, in `(-600, 600]`. Intervals are multiplied exactly, so a drift
+    /// means the melody has wandered by a comma, like a syntonic comma after I-IV-ii-V-I.
+    pub fn walk(&self, intervals: &[(u32, u32)]) -> (Hz, Cents) {
+        let octaves: f64 = intervals.iter()
+            .map(|&(n, d)| (f64::from(n) / f64::from(d)).log2())
+            .sum();
+
+        let end = Hz((f64::from(self.start.0) * octaves.exp2()) as f32);
+        let drift = Cents((1200.0 * octaves.rem_euclid(1.0)) as f32).nearest_unison_offset();
+        (end, drift)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test] fn comma_drift() {
+        let lattice = Lattice::new(Hz(264.0));
+
+        // C-F-D-G-C, with D a just minor third below F
+        let (end, drift) = lattice.walk(&[(4, 3), (5, 6), (4, 3), (2, 3)]);
+        assert!((drift - Cents::from_ratio(80.0 / 81.0)).0.abs() < 0.001);
+        assert!((end.0 - 264.0 * 80.0 / 81.0).abs() < 0.001);
+
+        let (end, drift) = lattice.walk(&[(3, 2), (4, 3), (2, 1)]);
+        assert_eq!(end, Hz(1056.0));
+        assert_eq!(drift, Cents(0.0));
+
+        assert_eq!(lattice.walk(&[]), (Hz(264.0), Cents(0.0)));
+        assert!((lattice.walk(&[(3, 2)]).1.0 + 498.045).abs() < 0.001);
+    }
+}
@@ -0,0 +1,112 @@
+//! MIDI Tuning Standard helpers
+
+use super::MidiTuning;
+use {Hz, Cents};
+
+/// The largest frequency value, `7F 7F 7E`. `7F 7F 7F` means "no change" in MTS.
+const MAX_VALUE: i64 = 127 * 16384 + 16382;
+
+/// Encodes a frequency as the MTS `[note, msb, lsb]` triple
+///
+/// `note` is the 12 EDO semitone below the frequency (A440 being note 69) and `msb`, `lsb`
+/// are the 7-bit halves of the offset above it in 1/16384 of a semitone. Frequencies
+/// outside of the MTS range (about 8.18 Hz to 13289.7 Hz) are clamped to its ends.
+pub fn encode_frequency(hz: Hz) -> [u8; 3] {
+    let semitones = 69.0 + 12.0 * (f64::from(hz.0) / 440.0).log2();
+    let value = (semitones * 16384.0).round();
+    let value = if value.is_nan() { 0 } else { (value as i64).clamp(0, MAX_VALUE) };
+
+    let frac = value % 16384;
+    [(value / 16384) as u8, (frac >> 7) as u8, (frac & 0x7f) as u8]
+}
+
+/// Decodes the MTS `[note, msb, lsb]` triple into a frequency
+pub fn decode_frequency(data: [u8; 3]) -> Hz {
+    let frac = (u32::from(data[1] & 0x7f) << 7) | u32::from(data[2] & 0x7f);
+    let semitones = f64::from(data[0] & 0x7f) + f64::from(frac) / 16384.0;
+    Hz((440.0 * ((semitones - 69.0) / 12.0).exp2()) as f32)
+}
+
+impl MidiTuning {
+    /// Encodes the octave from `base_note` as an MTS scale/octave tuning message (2-byte form)
+    ///
+    /// Every pitch class gets the offset of its note in the octave from 12 EDO with A440,
+    /// in the range of ±100 cents. The message is addressed to all devices and all channels.
+    ///
+    /// Notes of the octave that fall above 127 are taken octaves lower, so any `base_note` works.
+    pub fn mts_scale_octave(&self, base_note: u8) -> Vec<u8> {
+        let mut offsets = [0u16; 12];
+        for note in u16::from(base_note)..u16::from(base_note) + 12 {
+            // The highest MIDI note of the same pitch class
+            let note = if note > 127 { 127 - (139 - note % 12) % 12 } else { note } as u8;
+            let equal = Hz(440.0) + Cents((f32::from(note) - 69.0) * 100.0);
+            let offset = self.pitches[usize::from(note)] / equal;
+            let value = ((offset.0 / 100.0 + 1.0) * 8192.0).round();
+            offsets[usize::from(note % 12)] = if value.is_nan() { 0x2000 } else { value.clamp(0.0, 16383.0) as u16 };
+        }
+
+        let mut msg = vec![0xf0, 0x7e, 0x7f, 0x08, 0x09, 0x03, 0x7f, 0x7f];
+        for &value in &offsets {
+            msg.push((value >> 7) as u8);
+            msg.push((value & 0x7f) as u8);
+        }
+        msg.push(0xf7);
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test] fn round_trip() {
+        assert_eq!(encode_frequency(Hz(440.0)), [69, 0, 0]);
+        assert_eq!(decode_frequency([69, 0, 0]), Hz(440.0));
+        assert_eq!(encode_frequency(Hz(440.0) + ::Cents(50.0)), [69, 64, 0]);
+
+        for &freq in &[27.5, 261.6256, 432.0, 1000.0, 4186.01] {
+            let decoded = decode_frequency(encode_frequency(Hz(freq)));
+            // The resolution is about 0.006 cents
+            assert!((decoded / Hz(freq)).0.abs() < 0.01);
+        }
+    }
+
+    #[test] fn clamping() {
+        assert_eq!(encode_frequency(Hz(1.0)), [0, 0, 0]);
+        assert_eq!(encode_frequency(Hz(0.0)), [0, 0, 0]);
+        assert_eq!(encode_frequency(Hz(20000.0)), [127, 127, 126]);
+        assert_eq!(decode_frequency([0, 0, 0]).0.round(), 8.0);
+        assert_eq!(decode_frequency([127, 127, 126]).0.round(), 13290.0);
+    }
+
+    #[test] fn scale_octave() {
+        let msg = MidiTuning::default().mts_scale_octave(60);
+        assert_eq!(msg.len(), 33);
+        assert_eq!(msg[..8], [0xf0, 0x7e, 0x7f, 0x08, 0x09, 0x03, 0x7f, 0x7f]);
+        assert_eq!(msg[32], 0xf7);
+        assert!(msg[8..32].chunks(2).all(|pair| pair == [0x40, 0x00]));
+
+        // A just fifth from C is 2 cents sharp, on pitch class G
+        let tuning = MidiTuning::from_fn(|n| if n == 67 { Hz(261.6256 * 1.5) } else { MidiTuning::default()[usize::from(n)] });
+        let msg = tuning.mts_scale_octave(60);
+        let g = (u16::from(msg[8 + 14]) << 7) | u16::from(msg[8 + 15]);
+        assert_eq!(g, (8192.0_f32 * 1.01955).round() as u16);
+        assert_eq!(msg[8..22], MidiTuning::default().mts_scale_octave(60)[8..22]);
+
+        // High octaves wrap down by pitch class
+        let tuning = MidiTuning::from_fn(|n| {
+            let pitch = MidiTuning::default()[usize::from(n)];
+            if n == 127 || n == 116 { pitch + Cents(50.0) } else { pitch }
+        });
+        for &base in &[116, 117, 120, 127, 200, 255] {
+            let msg = tuning.mts_scale_octave(base);
+            assert_eq!(msg.len(), 33);
+            // Notes 127 (G) and 116 (G#) are a quarter tone sharp
+            assert_eq!(msg[8 + 14..8 + 18], [0x60, 0x00, 0x60, 0x00]);
+            assert_eq!(msg[8 + 12..8 + 14], [0x40, 0x00]);
+        }
+
+        let far = MidiTuning::from_fn(|n| MidiTuning::default()[usize::from(n)] + Cents(300.0));
+        assert!(far.mts_scale_octave(0)[8..32].chunks(2).all(|pair| pair == [0x7f, 0x7f]));
+    }
+}
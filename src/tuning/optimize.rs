@@ -0,0 +1,64 @@
+//! Tuning optimization
+
+use std::ops::RangeInclusive;
+
+use Cents;
+
+/// The largest number of generators stacked to reach a target
+const MAX_GENERATORS: i32 = 6;
+
+/// Finds the generator of a rank-2 temperament which best approximates target ratios
+///
+/// Tries `steps` evenly spaced generators over `range`. Every target `(num, den)` is
+/// approximated by up to 6 generators up or down, plus the closest number of periods,
+/// and the generator with the smallest RMS error (in cents) is returned along with the error.
+pub fn best_generator(period: Cents, targets: &[(u32, u32)], range: RangeInclusive<Cents>, steps: usize) -> (Cents, f32) {
+    let (lo, hi) = (range.start().0, range.end().0);
+    let targets: Vec<f32> = targets.iter()
+        .map(|&(n, d)| Cents::from_ratio(n as f32 / d as f32).0)
+        .collect();
+
+    (0..steps.max(1))
+        .map(|i| {
+            let t = if steps > 1 { i as f32 / (steps - 1) as f32 } else { 0.0 };
+            let generator = Cents(lo + (hi - lo) * t);
+            (generator, rms_error(generator, period, &targets))
+        })
+        .fold((Cents(lo), f32::INFINITY), |best, candidate| {
+            if candidate.1 < best.1 { candidate } else { best }
+        })
+}
+
+fn rms_error(generator: Cents, period: Cents, targets: &[f32]) -> f32 {
+    if targets.is_empty() { return 0.0 }
+
+    let sum: f32 = targets.iter().map(|&target| {
+        let error = (-MAX_GENERATORS..MAX_GENERATORS + 1)
+            .map(|k| {
+                let stacked = generator.0 * k as f32;
+                let periods = ((target - stacked) / period.0).round();
+                (stacked + periods * period.0 - target).abs()
+            })
+            .fold(f32::INFINITY, f32::min);
+        error * error
+    }).sum();
+
+    (sum / targets.len() as f32).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test] fn meantone() {
+        let targets = [(3, 2), (5, 4), (5, 3)];
+        let (generator, error) = best_generator(Cents(1200.0), &targets, Cents(680.0)..=Cents(720.0), 4001);
+        assert!(generator.0 > 695.0 && generator.0 < 698.0);
+        assert!(error < 5.0);
+
+        // Pythagorean tuning is the best for the fifth alone
+        let (generator, error) = best_generator(Cents(1200.0), &[(3, 2)], Cents(680.0)..=Cents(720.0), 4001);
+        assert!((generator - Cents::from_ratio(1.5)).0.abs() < 0.01);
+        assert!(error < 0.01);
+    }
+}
@@ -0,0 +1,107 @@
+//! Interchange with [Scale Workshop](https://sevish.com/scaleworkshop/)
+
+use super::{CyclicTuning, ParseError, parse_interval};
+use Hz;
+
+/// Exports a cyclic tuning as Scale Workshop compatible JSON
+///
+/// Every step becomes a cents line (like `"701.955001"`), the last one being the period.
+pub fn to_json(tuning: &CyclicTuning, name: &str) -> String {
+    let lines: Vec<String> = tuning.steps.iter()
+        .map(|c| format!("\"{:.6}\"", c.0))
+        .collect();
+
+    format!("{{\"name\":{},\"scale\":[{}]}}", json_string(name), lines.join(","))
+}
+
+/// Parses a scale pasted as Scale Workshop lines
+///
+/// Each line may be cents, a ratio or an EDO step (`7\12`), the last line is the period.
+/// Blank lines and `//` comments are skipped.
+pub fn parse_lines(text: &str, reference: Hz) -> Result<CyclicTuning, ParseError> {
+    let mut steps = Vec::new();
+    for line in text.lines() {
+        let line = match line.find("//") {
+            Some(pos) => &line[..pos],
+            None => line,
+        };
+        if line.trim().is_empty() { continue }
+
+        steps.push(parse_interval(line)?);
+    }
+
+    if steps.is_empty() { return Err(ParseError::Empty) }
+
+    Ok(CyclicTuning::from_cents(&steps, reference))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Cents;
+    use tuning::Tuning;
+
+    #[test] fn json_export() {
+        let tuning = CyclicTuning::from_cents(&[
+            Cents(400.0),
+            Cents(700.0),
+            Cents(1200.0)
+        ], Hz(440.0));
+
+        let json = to_json(&tuning, "Major \"triad\"");
+        assert_eq!(
+            json,
+            r#"{"name":"Major \"triad\"","scale":["400.000000","700.000000","1200.000000"]}"#
+        );
+
+        let tuning = CyclicTuning::from_cents(&[
+            Cents(203.91),
+            Cents(701.955),
+            Cents(1200.0)
+        ], Hz(440.0));
+
+        let json = to_json(&tuning, "C:\\just\n\tfifth\u{1}");
+        assert_eq!(
+            json,
+            r#"{"name":"C:\\just\n\tfifth\u0001","scale":["203.910004","701.955017","1200.000000"]}"#
+        );
+
+        let start = json.find("\"scale\":[").unwrap() + "\"scale\":[".len();
+        let end = start + json[start..].find(']').unwrap();
+        let entries: Vec<&str> = json[start..end].split(',').collect();
+        assert_eq!(entries.len(), tuning.steps.len());
+        assert!(entries.iter().all(|e| e.starts_with('"') && e.ends_with('"')));
+    }
+
+    #[test] fn lines_import() {
+        let text = "// a mixed scale\n\n9/8\n7\\12 // a fifth\n\n1100.0\n2\n";
+        let tuning = parse_lines(text, Hz(440.0)).unwrap();
+
+        assert_eq!(tuning.steps.len(), 4);
+        assert_eq!(tuning.steps[1], Cents(700.0));
+        assert_eq!(tuning.steps[2], Cents(1100.0));
+        assert_eq!(tuning.pitch(4).unwrap().0.round(), 880.0);
+        assert_eq!(tuning.pitch(1).unwrap().0.round(), 495.0);
+
+        assert_eq!(parse_lines("// nothing\n", Hz(440.0)).unwrap_err(), ParseError::Empty);
+        assert!(parse_lines("3/2\nfoo\n", Hz(440.0)).is_err());
+    }
+}
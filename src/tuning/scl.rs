@@ -0,0 +1,105 @@
+//! The [Scala](http://www.huygens-fokker.org/scala/scl_format.html) `.scl` scale format
+
+use super::{CyclicTuning, ParseError, parse_interval};
+use {Hz, Ratio};
+
+/// Writes a cyclic tuning as a `.scl` file
+///
+/// Steps are written as ratios (`3/2`) if the tuning carries exact ratios, and as cents otherwise.
+pub fn to_scl(tuning: &CyclicTuning, description: &str) -> String {
+    let mut out = format!("!\n{}\n {}\n!\n", description, tuning.steps.len());
+
+    match tuning.ratios {
+        Some(ref ratios) => for r in ratios {
+            out.push_str(&format!(" {}/{}\n", r.numer(), r.denom()))
+        },
+        None => for c in &tuning.steps {
+            out.push_str(&format!(" {:.6}\n", c.0))
+        },
+    }
+
+    out
+}
+
+/// Parses a `.scl` file
+///
+/// If every step is written as a ratio, the tuning keeps the exact ratios.
+pub fn parse_scl(text: &str, reference: Hz) -> Result<CyclicTuning, ParseError> {
+    let mut lines = text.lines().filter(|l| !l.starts_with('!'));
+
+    let _description = lines.next().ok_or(ParseError::Empty)?;
+    let count = lines.next().ok_or(ParseError::Empty)?;
+    let count: usize = count.trim().parse()
+        .map_err(|_| ParseError::InvalidCount(count.trim().to_owned()))?;
+
+    // The count comes from the file, so it's only trusted as far as there are lines
+    let mut steps = Vec::new();
+    let mut ratios = Some(Vec::new());
+    for line in lines.by_ref().take(count) {
+        steps.push(parse_interval(line)?);
+
+        let ratio = line.split_whitespace().next().and_then(parse_ratio);
+        ratios = match (ratios, ratio) {
+            (Some(mut rs), Some(r)) => { rs.push(r); Some(rs) },
+            _ => None,
+        };
+    }
+
+    if steps.len() != count {
+        return Err(ParseError::WrongCount { expected: count, found: steps.len() })
+    }
+
+    Ok(match ratios {
+        Some(ref ratios) if count > 0 => CyclicTuning::from_exact_ratios(ratios, reference),
+        _ => CyclicTuning::from_cents(&steps, reference),
+    })
+}
+
+fn parse_ratio(token: &str) -> Option<Ratio> {
+    let (num, den) = match token.find('/') {
+        Some(pos) => (&token[..pos], &token[pos + 1..]),
+        None => (token, "1"),
+    };
+
+    match (num.parse(), den.parse()) {
+        (Ok(num), Ok(den)) if num != 0 && den != 0 => Some(Ratio::new(num, den)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Cents;
+
+    #[test] fn ratios_round_trip() {
+        let ratios = [Ratio::new(5, 4), Ratio::new(3, 2), Ratio::new(2, 1)];
+        let tuning = CyclicTuning::from_exact_ratios(&ratios, Hz(440.0));
+
+        let scl = to_scl(&tuning, "Just major triad");
+        assert_eq!(scl, "!\nJust major triad\n 3\n!\n 5/4\n 3/2\n 2/1\n");
+
+        let parsed = parse_scl(&scl, Hz(440.0)).unwrap();
+        assert_eq!(parsed.ratios(), Some(&ratios[..]));
+        assert_eq!(to_scl(&parsed, "Just major triad"), scl);
+    }
+
+    #[test] fn cents_export() {
+        let tuning = CyclicTuning::from_cents(&[Cents(700.0), Cents(1200.0)], Hz(440.0));
+        assert_eq!(to_scl(&tuning, "Fifths"), "!\nFifths\n 2\n!\n 700.000000\n 1200.000000\n");
+
+        let parsed = parse_scl("! mixed.scl\n!\nMixed\n 2\n!\n 700.0\n 2/1\n", Hz(440.0)).unwrap();
+        assert_eq!(parsed.ratios(), None);
+        assert_eq!(parsed.steps, vec![Cents(700.0), Cents(1200.0)]);
+
+        let short = parse_scl("Short\n 3\n 3/2\n 2/1\n", Hz(440.0));
+        assert_eq!(short.unwrap_err(), ParseError::WrongCount { expected: 3, found: 2 });
+    }
+
+    #[test] fn huge_count() {
+        assert_eq!(
+            parse_scl("x\n 99999999999999\n 3/2\n", Hz(440.0)).unwrap_err(),
+            ParseError::WrongCount { expected: 99999999999999, found: 1 }
+        );
+    }
+}
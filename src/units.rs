@@ -0,0 +1,82 @@
+//! Units-aware serde representations of `Hz` and `Cents`
+//!
+//! Bare floats are ambiguous in human-edited files, so these modules write values tagged with
+//! their unit, like `{ hz = 440.0 }` or `{ cents = 700.0 }` in TOML:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "monochord::units::hz")]
+//!     reference: Hz,
+//!     #[serde(with = "monochord::units::cents")]
+//!     fifth: Cents,
+//! }
+//! ```
+
+/// Serializes `Hz` as `{ hz = <value> }`
+pub mod hz {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use Hz;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Tagged {
+        hz: f32,
+    }
+
+    pub fn serialize<S: Serializer>(value: &Hz, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged { hz: value.0 }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Hz, D::Error> {
+        Tagged::deserialize(deserializer).map(|t| Hz(t.hz))
+    }
+}
+
+/// Serializes `Cents` as `{ cents = <value> }`
+pub mod cents {
+    use serde::{Serialize, Serializer, Deserialize, Deserializer};
+    use Cents;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Tagged {
+        cents: f32,
+    }
+
+    pub fn serialize<S: Serializer>(value: &Cents, serializer: S) -> Result<S::Ok, S::Error> {
+        Tagged { cents: value.0 }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Cents, D::Error> {
+        Tagged::deserialize(deserializer).map(|t| Cents(t.cents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Deserialize};
+    use toml;
+    use {Hz, Cents};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "::units::hz")]
+        reference: Hz,
+        #[serde(with = "::units::cents")]
+        fifth: Cents,
+    }
+
+    #[test] fn toml_round_trip() {
+        let config = Config { reference: Hz(440.0), fifth: Cents(701.955) };
+        let text = toml::to_string(&config).unwrap();
+        assert!(text.contains("[reference]\nhz = 440.0"), "{}", text);
+        assert!(text.contains("[fifth]\ncents = 701.955"), "{}", text);
+        assert_eq!(toml::from_str::<Config>(&text).unwrap(), config);
+
+        let inline = "reference = { hz = 432.0 }\nfifth = { cents = 700.0 }\n";
+        assert_eq!(toml::from_str::<Config>(inline).unwrap(), Config { reference: Hz(432.0), fifth: Cents(700.0) });
+        assert!(toml::from_str::<Config>("reference = 440.0\nfifth = { cents = 700.0 }\n").is_err());
+        assert!(toml::from_str::<Config>("reference = { cents = 440.0 }\nfifth = { cents = 700.0 }\n").is_err());
+    }
+}